@@ -0,0 +1,134 @@
+use crate::{io, sim};
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: tortoise compare <scenario.yaml> <scenario.yaml>... [--seed <seed>]";
+const MIGRATE_USAGE: &str = "usage: tortoise migrate <data dir>";
+
+/// Flags that apply to the app as well as to every subcommand.
+#[derive(Debug, Default, PartialEq)]
+pub struct GlobalOptions {
+    pub data_dir: Option<PathBuf>,
+    pub portable: bool,
+}
+
+/// Splits `--data-dir <dir>` and `--portable` out of `args`, returning them and the remaining
+/// arguments.
+pub fn global_options(args: &[String]) -> Result<(GlobalOptions, Vec<String>), String> {
+    let mut options = GlobalOptions::default();
+    let mut rest = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => {
+                let dir = args.next().ok_or("usage: --data-dir <dir>".to_string())?;
+                options.data_dir = Some(PathBuf::from(dir));
+            }
+            "--portable" => options.portable = true,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    Ok((options, rest))
+}
+
+/// Runs the command line subcommand named by `args`, if there is one, and returns its exit code.
+/// Without a subcommand, returns `None` so that the app starts as usual.
+pub fn run(args: &[String]) -> Option<i32> {
+    let result = match args.get(1).map(String::as_str) {
+        Some("compare") => compare(&args[2..]),
+        Some("migrate") => migrate(&args[2..]),
+        _ => return None,
+    };
+    Some(match result {
+        Ok(output) => {
+            println!("{}", output);
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    })
+}
+
+/// Copies the saved data to a new data dir, which is used from the next start.
+fn migrate(args: &[String]) -> Result<String, String> {
+    let to = match args {
+        [to] => PathBuf::from(to),
+        _ => return Err(MIGRATE_USAGE.to_string()),
+    };
+    let copied = io::migrate_data_dir(&to).map_err(|e| e.to_string())?;
+    Ok(format!("Copied {} files to {}", copied, to.display()))
+}
+
+/// Compares the scenarios in the given YAML (or JSON) files against the first one, and returns
+/// the comparison as JSON.
+fn compare(args: &[String]) -> Result<String, String> {
+    let mut seed: Option<u64> = None;
+    let mut scenarios: Vec<sim::Scenario> = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            let value = args.next().ok_or(USAGE.to_string())?;
+            seed = Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid seed: {}", value))?,
+            );
+            continue;
+        }
+        let contents =
+            std::fs::read_to_string(arg).map_err(|e| format!("Could not read {}: {}", arg, e))?;
+        let scenario = serde_yaml::from_str(&contents)
+            .map_err(|e| format!("Could not parse {}: {}", arg, e))?;
+        scenarios.push(scenario);
+    }
+    if scenarios.len() < 2 {
+        return Err(USAGE.to_string());
+    }
+
+    let comparison = sim::compare::compare(&scenarios, seed).map_err(|e| e.to_string())?;
+    Ok(serde_json::to_string_pretty(&comparison).unwrap())
+}
+
+#[test]
+fn test_compare_command() {
+    let dir = std::env::temp_dir().join("tortoise_compare_command");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut paths = vec![];
+    for (i, name) in ["renting", "buying"].iter().enumerate() {
+        let mut scenario = sim::examples::mortgage_scenario::mortgage_scenario();
+        if i == 0 {
+            scenario.loans = vec![];
+        }
+        let path = dir.join(format!("{}.yaml", name));
+        std::fs::write(&path, serde_yaml::to_string(&scenario).unwrap()).unwrap();
+        paths.push(path.to_str().unwrap().to_string());
+    }
+
+    let args: Vec<String> = ["tortoise", "compare", &paths[0], &paths[1], "--seed", "3"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let output = compare(&args[2..]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["seed"], 3);
+    assert_eq!(json["deltas"][0]["diff"]["fields"][0], "loans");
+
+    assert_eq!(run(&args[..1]), None);
+    assert!(compare(&args[2..3]).is_err());
+}
+
+#[test]
+fn test_global_options() {
+    let args: Vec<String> = ["tortoise", "--data-dir", "/plans", "compare", "--portable"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let (options, rest) = global_options(&args).unwrap();
+    assert_eq!(options.data_dir, Some(PathBuf::from("/plans")));
+    assert!(options.portable);
+    assert_eq!(rest, vec!["tortoise".to_string(), "compare".to_string()]);
+
+    assert!(global_options(&args[..2]).is_err());
+    assert!(migrate(&[]).is_err());
+}
@@ -1,10 +1,19 @@
+use crate::error::Error;
+use crate::import::csv::{self, CsvImport, CsvMapping};
+use crate::import::ofx::{self, OfxStatement};
+use crate::import::recurring::{self, RecurringFlow};
+use crate::import::Transaction;
+use crate::sim::cash::Account;
+use crate::sim::portfolio::Portfolio;
+use chrono::NaiveDate;
 use serde_json::{json, Value};
+use std::fs;
 use std::sync::Mutex;
 
 static FILE_PATH: Mutex<Option<String>> = Mutex::new(None);
 
 #[tauri::command]
-pub async fn import_account(file_path: &str, handle: tauri::AppHandle) -> Result<Value, String> {
+pub async fn import_account(file_path: &str, handle: tauri::AppHandle) -> Result<Value, Error> {
     *FILE_PATH.lock().unwrap() = Some(file_path.to_string());
 
     let window = tauri::WebviewWindowBuilder::new(
@@ -16,17 +25,58 @@ pub async fn import_account(file_path: &str, handle: tauri::AppHandle) -> Result
     .inner_size(1080., 720.)
     .build();
 
-    match window {
-        Ok(_w) => {}
-        Err(_e) => {
-            return Err("Error creating account import window".to_string());
-        }
+    if let Err(e) = window {
+        return Err(Error::Io(format!(
+            "Error creating account import window: {}",
+            e
+        )));
     }
 
     Ok(json!("{\"status\": \"ok\"}"))
 }
 
 #[tauri::command]
-pub async fn get_file_path() -> Result<Value, String> {
+pub async fn get_file_path() -> Result<Value, Error> {
     Ok(json!(*FILE_PATH.lock().unwrap()))
 }
+
+#[tauri::command]
+pub async fn read_csv_headers(file_path: &str, delimiter: char) -> Result<Vec<String>, Error> {
+    csv::read_headers(&fs::read_to_string(file_path)?, delimiter)
+}
+
+#[tauri::command]
+pub async fn import_csv_transactions(
+    file_path: &str,
+    mapping: CsvMapping,
+) -> Result<CsvImport, Error> {
+    mapping.import(&fs::read_to_string(file_path)?)
+}
+
+#[tauri::command]
+pub async fn detect_recurring_flows(
+    transactions: Vec<Transaction>,
+) -> Result<Vec<RecurringFlow>, Error> {
+    Ok(recurring::detect_recurring(&transactions))
+}
+
+#[tauri::command]
+pub async fn import_ofx_statements(file_path: &str) -> Result<Vec<OfxStatement>, Error> {
+    ofx::parse(&ofx::text(&fs::read(file_path)?))
+}
+
+#[tauri::command]
+pub async fn account_from_ofx_statement(
+    statement: OfxStatement,
+    name: String,
+    end_date: NaiveDate,
+) -> Result<Account, Error> {
+    Ok(statement.to_account(&name, end_date))
+}
+
+#[tauri::command]
+pub async fn portfolio_from_ofx_statement(
+    statement: OfxStatement,
+) -> Result<Option<Portfolio>, Error> {
+    Ok(statement.to_portfolio())
+}
@@ -1,2 +1,3 @@
+pub mod cli;
 pub mod sim;
 pub mod import;
\ No newline at end of file
@@ -1,88 +1,61 @@
 use serde_json::{json, Value};
 
+use crate::error::Error;
 use crate::io;
 use crate::sim;
-use std::{ffi::OsStr, fs};
-
-fn get_file_names(contains: &str) -> Vec<String> {
-    let dir_path = io::get_or_create_save_dir();
-    let yaml_files = fs::read_dir(dir_path)
-        .unwrap()
-        .filter_map(|entry| {
-            let entry = entry.unwrap();
-            let path = entry.path();
-            if path.is_file()
-                && path.extension()? == OsStr::new("yaml")
-                && path.file_name()?.to_str()?.contains(contains)
-            {
-                Some(path)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-
-    let file_names = yaml_files
-        .iter()
-        .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
-        .collect::<Vec<_>>();
-
-    file_names
-}
 
 #[tauri::command]
-pub async fn list_available_accounts() -> Result<Value, String> {
-    let account_names = io::list_accounts();
+pub async fn list_available_accounts() -> Result<Value, Error> {
+    let account_names = io::list_accounts()?;
     Ok(json!(&account_names))
 }
 
 #[tauri::command]
-pub async fn list_available_accounts_detail() -> Result<Value, String> {
-    let account_details = io::list_accounts_detail();
+pub async fn list_available_accounts_detail() -> Result<Value, Error> {
+    let account_details = io::list_accounts_detail()?;
     Ok(json!(&account_details))
 }
 
 #[tauri::command]
-pub async fn list_available_portfolios() -> Result<Value, String> {
-    let file_names = get_file_names("portfolio");
-    Ok(json!(&file_names))
+pub async fn list_available_portfolios() -> Result<Value, Error> {
+    Ok(json!(&io::list_portfolios()?))
+}
+
+#[tauri::command]
+pub async fn list_available_portfolios_detail() -> Result<Value, Error> {
+    Ok(json!(&io::list_portfolios_detail()?))
 }
 
-fn load_config<T>(account_filename: String) -> Result<T, serde_yaml::Error>
-where
-    T: serde::de::DeserializeOwned,
-    T: Clone,
-{
-    let dir = io::get_or_create_save_dir();
-    let account_str =
-        fs::read_to_string(dir.join(account_filename.clone())).expect("Could not read file");
-    if account_filename.ends_with(".yaml") {
-        return serde_yaml::from_str(&account_str);
-    } else {
-        panic!("Unknown file type: {}", account_filename);
-    }
+#[tauri::command]
+pub async fn get_portfolio_config(portfolio_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::read_portfolio(&portfolio_name)?))
+}
+
+#[tauri::command]
+pub async fn save_portfolio_config(
+    portfolio_name: String,
+    portfolio: sim::portfolio::Portfolio,
+) -> Result<(), Error> {
+    io::write_portfolio_file(&portfolio_name, &portfolio)
+}
+
+#[tauri::command]
+pub async fn delete_portfolio(portfolio_name: String) -> Result<(), Error> {
+    io::delete_portfolio(&portfolio_name)
 }
 
 #[tauri::command]
 pub async fn run_account_simulation(
     account_name: String,
-    portfolio_filename: Option<String>,
-) -> Result<Value, String> {
-    let account = io::read_account(&account_name);
-
-    if !account.is_ok() {
-        return Err("{\"error\": \"Error loading account\"}".to_string());
-    }
-
-    let portfolio = match portfolio_filename {
-        Some(p) => {
-            Some(load_config::<sim::portfolio::Portfolio>(p).expect("Could not load portfolio"))
-        }
+    portfolio_name: Option<String>,
+) -> Result<Value, Error> {
+    let acc = io::read_account(&account_name)?;
+
+    let portfolio = match portfolio_name {
+        Some(p) => Some(io::read_portfolio(&p)?),
         None => sim::portfolio::Portfolio::default(),
     };
 
-    let acc = account.unwrap();
-
     let scenario = sim::Scenario {
         accounts: vec![sim::InvestedAccount {
             account: acc.clone(),
@@ -91,99 +64,364 @@ pub async fn run_account_simulation(
         start_date: acc.start_date.clone(),
         end_date: acc.end_date.clone(),
         transfers: vec![],
+        loans: vec![],
+        withdrawals: vec![],
+        goals: vec![],
+        household: None,
+        longevity: None,
+        market: sim::historical::MarketModel::Random,
         num_samples: 1,
+        inflation: 0.0,
+        seed: None,
     };
 
-    let response = sim::run_simulation(scenario);
+    Ok(json!(&sim::run_simulation(scenario)?))
+}
+
+#[tokio::test]
+async fn test_get_results_fails_cleanly() {
+    io::init_test_dirs();
+    let r = run_account_simulation("Not A Real Scenario 1234".to_string(), None).await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
+}
+
+#[tauri::command]
+pub async fn get_cash_flows_from_config(account_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::read_account(&account_name)?.cash_flows))
+}
+
+#[tauri::command]
+pub async fn get_account_config(account_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::read_account(&account_name)?))
+}
+
+#[tauri::command]
+pub async fn save_account_config(account: String) -> Result<(), Error> {
+    let account: sim::cash::Account = serde_json::from_str(&account)?;
+    sim::validation::check(account.validate())?;
+    io::write_account_file(&account)
+}
+
+/// The problems with an account being edited, by field, without saving it.
+#[tauri::command]
+pub async fn validate_account(account: String) -> Result<Value, Error> {
+    let account: sim::cash::Account = serde_json::from_str(&account)?;
+    Ok(json!(&account.validate()))
+}
 
-    if !response.is_ok() {
-        return Err("{\"error\": \"Error running simulation\"}".to_string());
-    }
+#[tauri::command]
+pub async fn validate_portfolio(portfolio: sim::portfolio::Portfolio) -> Result<Value, Error> {
+    Ok(json!(&portfolio.validate()))
+}
 
-    // let r = serde_json::to_string(&response.unwrap()).unwrap();
-    Ok(json!(&response.unwrap()))
+#[tauri::command]
+pub async fn validate_scenario(scenario: sim::Scenario) -> Result<Value, Error> {
+    Ok(json!(&scenario.validate()))
 }
 
 #[tokio::test]
-async fn test_get_results_fails_cleanly() {
-    let _r = run_account_simulation("Not A Real Scenario 1234".to_string(), None).await;
+async fn test_save_account_config_fails_cleanly() {
+    io::init_test_dirs();
+    let r = save_account_config("{\"name\": 1}".to_string()).await;
+    assert!(matches!(r, Err(Error::Parse(_))));
+
+    let mut account = sim::examples::simple_account::simple_account();
+    account.name = String::new();
+    let json = serde_json::to_string(&account).unwrap();
+    let r = save_account_config(json.clone()).await;
+    assert!(matches!(r, Err(Error::Validation(_))));
+    let problems = validate_account(json).await.unwrap();
+    assert_eq!(problems[0]["path"], "name");
+}
+
+#[tauri::command]
+pub async fn delete_account(account_name: String) -> Result<(), Error> {
+    io::delete_account(&account_name)
+}
+
+#[tauri::command]
+pub async fn list_deleted_accounts() -> Result<Value, Error> {
+    Ok(json!(&io::list_deleted_accounts()?))
+}
+
+#[tauri::command]
+pub async fn restore_deleted_account(tombstone: String) -> Result<Value, Error> {
+    Ok(json!(&io::restore_deleted_account(&tombstone)?))
+}
+
+#[tauri::command]
+pub async fn purge_deleted_account(tombstone: String) -> Result<(), Error> {
+    io::purge_deleted_account(&tombstone)
+}
+
+#[tauri::command]
+pub async fn list_account_versions(account_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::list_account_versions(&account_name)?))
+}
+
+#[tauri::command]
+pub async fn get_account_version(account_name: String, version: String) -> Result<Value, Error> {
+    Ok(json!(&io::read_account_version(&account_name, &version)?))
+}
+
+#[tauri::command]
+pub async fn list_account_versions_to_prune(account_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::list_account_versions_to_prune(&account_name)?))
+}
+
+#[tauri::command]
+pub async fn get_app_config() -> Result<Value, Error> {
+    Ok(json!(&io::read_app_config()))
+}
+
+#[tauri::command]
+pub async fn save_app_config(config: crate::config::AppConfig) -> Result<(), Error> {
+    io::write_app_config(&config)
+}
+
+#[tauri::command]
+pub async fn get_data_dir() -> Result<Value, Error> {
+    Ok(json!(&io::get_or_create_save_dir()?))
+}
+
+#[tauri::command]
+pub async fn migrate_data_dir(to: String) -> Result<Value, Error> {
+    Ok(json!(io::migrate_data_dir(std::path::Path::new(&to))?))
+}
+
+#[tauri::command]
+pub async fn diff_account_versions(
+    account_name: String,
+    from: String,
+    to: String,
+) -> Result<Value, Error> {
+    Ok(json!(&io::diff_account_versions(
+        &account_name,
+        &from,
+        &to
+    )?))
+}
+
+#[tauri::command]
+pub async fn restore_account_version(
+    account_name: String,
+    version: String,
+) -> Result<Value, Error> {
+    Ok(json!(&io::restore_account_version(
+        &account_name,
+        &version
+    )?))
+}
+
+fn scenario_from_account_names(account_names: Vec<String>) -> Result<sim::Scenario, Error> {
+    let accounts = account_names
+        .iter()
+        .map(|name| io::read_account(name))
+        .collect::<Result<Vec<sim::cash::Account>, Error>>()?;
+
+    // The scenario spans every account
+    let start_date = accounts.iter().map(|a| a.start_date).min();
+    let end_date = accounts.iter().map(|a| a.end_date).max();
+    let (Some(start_date), Some(end_date)) = (start_date, end_date) else {
+        return Err(Error::Validation(
+            "Unable to infer simulation date range from accounts".to_string(),
+        ));
+    };
+
+    Ok(sim::Scenario::from_accounts(
+        accounts, start_date, end_date, 100,
+    ))
+}
+
+#[tauri::command]
+pub async fn run_scenario_simulation(account_names: Vec<String>) -> Result<Value, Error> {
+    let scenario = scenario_from_account_names(account_names)?;
+    Ok(json!(&sim::run_simulation(scenario)?))
+}
+
+#[tokio::test]
+async fn test_run_scenario_simulation_fails_cleanly() {
+    io::init_test_dirs();
+    let r = run_scenario_simulation(vec![]).await;
+    assert!(matches!(r, Err(Error::Validation(_))));
+    let r = run_scenario_simulation(vec!["Not A Real Account 1234".to_string()]).await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
 }
 
 #[tauri::command]
-pub async fn get_cash_flows_from_config(account_name: String) -> Result<Value, String> {
-    let account = io::read_account(&account_name);
+pub async fn list_saved_scenarios() -> Result<Value, Error> {
+    Ok(json!(&io::list_scenarios()?))
+}
 
-    if !account.is_ok() {
-        return Err("{\"error\": \"Error loading account\"}".to_string());
-    }
+#[tauri::command]
+pub async fn get_saved_scenario(scenario_name: String) -> Result<Value, Error> {
+    Ok(json!(&io::read_scenario(&scenario_name)?))
+}
 
-    Ok(json!(&account.unwrap().cash_flows))
+#[tauri::command]
+pub async fn save_scenario(scenario_name: String, scenario: sim::Scenario) -> Result<(), Error> {
+    io::write_scenario_file(&scenario_name, &scenario)
 }
 
 #[tauri::command]
-pub async fn get_account_config(account_name: String) -> Result<Value, String> {
-    let account = io::read_account(&account_name);
+pub async fn delete_saved_scenario(scenario_name: String) -> Result<(), Error> {
+    io::delete_scenario(&scenario_name)
+}
 
-    if !account.is_ok() {
-        return Err("{\"error\": \"Error loading account\"}".to_string());
-    }
+#[tauri::command]
+pub async fn run_saved_scenario(scenario_name: String) -> Result<Value, Error> {
+    let scenario = io::read_scenario(&scenario_name)?;
+    Ok(json!(&sim::run_simulation(scenario)?))
+}
 
-    Ok(json!(&account.unwrap()))
+#[tokio::test]
+async fn test_run_saved_scenario_fails_cleanly() {
+    io::init_test_dirs();
+    let r = run_saved_scenario("Not A Real Scenario 1234".to_string()).await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
 }
 
 #[tauri::command]
-pub async fn save_account_config(account: String) -> Result<(), String> {
-    let account: sim::cash::Account = serde_json::from_str(&account).unwrap();
-    io::write_account_file(&account);
-    Ok(())
+pub async fn compare_debt_strategies(
+    loans: String,
+    monthly_budget: f64,
+    strategies: Vec<sim::debt::PayoffStrategy>,
+) -> Result<Value, Error> {
+    let loans: Vec<sim::loan::Loan> = serde_json::from_str(&loans)?;
+    Ok(json!(&sim::debt::compare_strategies(
+        &loans,
+        monthly_budget,
+        &strategies
+    )?))
 }
 
 #[tauri::command]
-pub async fn delete_account(account_name: String) -> Result<(), String> {
-    let r = io::delete_account(&account_name);
-    if r.is_err() {
-        return Err("{\"error\": \"Error deleting account\"}".to_string());
-    }
-    Ok(())
+pub async fn solve_scenario(
+    scenario_name: String,
+    variable: sim::solver::SolveFor,
+    metric: sim::metrics::Metric,
+    target: f64,
+    lower: f64,
+    upper: f64,
+    tolerance: f64,
+) -> Result<Value, Error> {
+    let scenario = io::read_scenario(&scenario_name)?;
+    Ok(json!(&sim::solver::solve(
+        &scenario, &variable, &metric, target, lower, upper, tolerance,
+    )?))
+}
+
+#[tokio::test]
+async fn test_solve_scenario_fails_cleanly() {
+    io::init_test_dirs();
+    let r = solve_scenario(
+        "Not A Real Scenario 1234".to_string(),
+        sim::solver::SolveFor::AccountEndDate {
+            account: "Example".to_string(),
+        },
+        sim::metrics::Metric::SuccessProbability {
+            account: "Example".to_string(),
+        },
+        0.9,
+        0.0,
+        0.1,
+        0.001,
+    )
+    .await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
 }
 
 #[tauri::command]
-pub async fn run_scenario_simulation(account_names: Vec<String>) -> Result<Value, String> {
-    let mut accounts: Vec<sim::cash::Account> = Vec::new();
-    let mut start_date: Option<chrono::NaiveDate> = None;
-    let mut end_date: Option<chrono::NaiveDate> = None;
+pub async fn analyze_sensitivity(
+    scenario_name: String,
+    metric: sim::metrics::Metric,
+    perturbation: f64,
+) -> Result<Value, Error> {
+    let scenario = io::read_scenario(&scenario_name)?;
+    Ok(json!(&sim::sensitivity::analyze(
+        &scenario,
+        &metric,
+        perturbation
+    )?))
+}
 
-    for account_name in account_names {
-        let account = io::read_account(&account_name);
-        let account_start_date = account.as_ref().unwrap().start_date;
-        let account_end_date = account.as_ref().unwrap().end_date;
+#[tokio::test]
+async fn test_analyze_sensitivity_fails_cleanly() {
+    io::init_test_dirs();
+    let metric = sim::metrics::Metric::SuccessProbability {
+        account: "Example".to_string(),
+    };
+    let r = analyze_sensitivity("Not A Real Scenario 1234".to_string(), metric, 0.1).await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
+}
 
-        if account.is_ok() {
-            accounts.push(account.unwrap());
-            if start_date.is_none() || account_start_date < start_date.unwrap() {
-                start_date = Some(account_start_date);
-            }
-            if end_date.is_none() || account_end_date > end_date.unwrap() {
-                end_date = Some(account_end_date);
-            }
-        }
-    }
+#[tauri::command]
+pub async fn compare_scenarios(
+    scenario_names: Vec<String>,
+    seed: Option<u64>,
+) -> Result<Value, Error> {
+    let scenarios = scenario_names
+        .iter()
+        .map(|name| io::read_scenario(name))
+        .collect::<Result<Vec<sim::Scenario>, Error>>()?;
+    Ok(json!(&sim::compare::compare(&scenarios, seed)?))
+}
 
-    if (end_date.is_none() || start_date.is_none()) && accounts.len() > 0 {
-        return Err(
-            "{\"error\": \"Unable to infer simulation date range from accounts.\"}".to_string(),
-        );
-    }
+#[tokio::test]
+async fn test_compare_scenarios_fails_cleanly() {
+    io::init_test_dirs();
+    let r = compare_scenarios(vec![], None).await;
+    assert!(matches!(r, Err(Error::Validation(_))));
+    let r = compare_scenarios(vec!["Not A Real Scenario 1234".to_string()], None).await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
+}
 
-    let scenario =
-        sim::Scenario::from_accounts(accounts, start_date.unwrap(), end_date.unwrap(), 100);
+#[tauri::command]
+pub async fn list_returns_datasets() -> Result<Value, Error> {
+    Ok(json!(&io::list_returns_datasets()?))
+}
 
-    let response = sim::run_simulation(scenario);
+#[tauri::command]
+pub async fn run_historical_replay(
+    scenario_name: String,
+    dataset: String,
+    window_start: chrono::NaiveDate,
+    from: Option<chrono::NaiveDate>,
+) -> Result<Value, Error> {
+    let mut scenario = io::read_scenario(&scenario_name)?;
+    let dataset = io::read_returns_dataset(&dataset)?;
+    scenario.market = sim::historical::MarketModel::Historical(sim::historical::HistoricalReplay {
+        dataset,
+        window_start,
+        from: from.unwrap_or(scenario.start_date),
+    });
+    Ok(json!(&sim::run_simulation(scenario)?))
+}
 
-    if !response.is_ok() {
-        return Err("{\"error\": \"Error running simulation\"}".to_string());
-    }
+#[tauri::command]
+pub async fn run_rolling_replay(
+    scenario_name: String,
+    dataset: String,
+    metric: sim::metrics::Metric,
+) -> Result<Value, Error> {
+    let scenario = io::read_scenario(&scenario_name)?;
+    let dataset = io::read_returns_dataset(&dataset)?;
+    Ok(json!(&sim::historical::rolling_replay(
+        &scenario, &dataset, &metric
+    )?))
+}
 
-    // let r = serde_json::to_string(&response.unwrap()).unwrap();
-    Ok(json!(&response.unwrap()))
+#[tokio::test]
+async fn test_run_rolling_replay_fails_cleanly() {
+    io::init_test_dirs();
+    let metric = sim::metrics::Metric::SuccessProbability {
+        account: "Example".to_string(),
+    };
+    let r = run_rolling_replay(
+        "Not A Real Scenario 1234".to_string(),
+        "US Stocks and Bonds".to_string(),
+        metric,
+    )
+    .await;
+    assert!(matches!(r, Err(Error::NotFound(_))));
 }
@@ -0,0 +1,187 @@
+use crate::io::Version;
+use crate::sim::validation::{nested, Problem};
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+/// App-wide settings, saved as `config.yaml` in the OS config dir, or next to the executable in
+/// portable mode.
+///
+/// `data_dir` moves saved data elsewhere, such as a synced folder. It takes effect on the next
+/// start, and the `--data-dir` flag and `TORTOISE_DATA_DIR` environment variable override it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct AppConfig {
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    #[serde(default)]
+    pub storage: StorageBackend,
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub data_dir: Option<PathBuf>,
+}
+
+impl AppConfig {
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = nested("retention", self.retention.validate());
+        if self.storage == StorageBackend::Sqlite && !cfg!(feature = "sqlite") {
+            problems.push(Problem::new("storage", "is not available in this build"));
+        }
+        problems
+    }
+}
+
+/// Where accounts, scenarios and portfolios are saved. `Sqlite` needs a build with the `sqlite`
+/// feature, and can't be chosen otherwise. Takes effect on the next start.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum StorageBackend {
+    #[default]
+    Filesystem,
+    Sqlite,
+}
+
+/// A grandfather-father-son policy for how many saved versions of an account to keep.
+///
+/// The `keep_recent` newest versions are always kept, and at least the newest one even if
+/// `keep_recent` is 0 in a hand-edited config. Older versions are kept if they are the
+/// last of their day and less than `keep_daily_days` days old, or the last of their month and
+/// less than `keep_monthly_months` months old. Every other version is pruned.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct RetentionPolicy {
+    pub keep_recent: usize,
+    pub keep_daily_days: u32,
+    pub keep_monthly_months: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            keep_recent: 10,
+            keep_daily_days: 30,
+            keep_monthly_months: 12,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Vec<Problem> {
+        match self.keep_recent {
+            0 => vec![Problem::new(
+                "keep_recent",
+                "must keep at least the newest version",
+            )],
+            _ => vec![],
+        }
+    }
+
+    /// The versions the policy would remove as of `now`, oldest first.
+    pub fn versions_to_prune(&self, versions: &[Version], now: NaiveDateTime) -> Vec<Version> {
+        let mut newest_first = versions.to_vec();
+        newest_first.sort_by_key(|v| std::cmp::Reverse(v.saved_at));
+
+        let months = |d: &NaiveDateTime| d.year() * 12 + d.month0() as i32;
+        let mut days_seen = HashSet::new();
+        let mut months_seen = HashSet::new();
+        let mut pruned = vec![];
+        for (i, v) in newest_first.into_iter().enumerate() {
+            let last_of_day = days_seen.insert(v.saved_at.date());
+            let last_of_month = months_seen.insert(months(&v.saved_at));
+
+            let keep = i < self.keep_recent.max(1)
+                || (last_of_day
+                    && (now.date() - v.saved_at.date()).num_days() < self.keep_daily_days as i64)
+                || (last_of_month
+                    && months(&now) - months(&v.saved_at) < self.keep_monthly_months as i32);
+            if !keep {
+                pruned.push(v);
+            }
+        }
+        pruned.reverse();
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn version(y: i32, m: u32, d: u32, h: u32) -> Version {
+        let saved_at = NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap();
+        Version {
+            version: saved_at.format("%Y%m%d_%H%M%S").to_string(),
+            saved_at,
+        }
+    }
+
+    #[test]
+    fn test_versions_to_prune() {
+        let policy = RetentionPolicy {
+            keep_recent: 2,
+            keep_daily_days: 7,
+            keep_monthly_months: 3,
+        };
+        let versions = vec![
+            version(2023, 12, 20, 9), // Too old for a monthly version
+            version(2024, 2, 10, 9),
+            version(2024, 2, 11, 9), // Last of February
+            version(2024, 4, 20, 9),
+            version(2024, 4, 20, 17), // Last of April 20th
+            version(2024, 4, 24, 9),
+            version(2024, 4, 25, 9),  // Recent
+            version(2024, 4, 25, 12), // Recent
+        ];
+        let now = NaiveDate::from_ymd_opt(2024, 4, 25)
+            .unwrap()
+            .and_hms_opt(18, 0, 0)
+            .unwrap();
+
+        let pruned = policy.versions_to_prune(&versions, now);
+        assert_eq!(
+            pruned,
+            vec![
+                versions[0].clone(),
+                versions[1].clone(),
+                versions[3].clone()
+            ]
+        );
+
+        let keep_all = RetentionPolicy {
+            keep_recent: versions.len(),
+            ..policy
+        };
+        assert!(keep_all.versions_to_prune(&versions, now).is_empty());
+
+        // The newest version is never pruned
+        let keep_none = RetentionPolicy {
+            keep_recent: 0,
+            keep_daily_days: 0,
+            keep_monthly_months: 0,
+        };
+        assert!(!keep_none.validate().is_empty());
+        let pruned = keep_none.versions_to_prune(&versions, now);
+        assert_eq!(pruned.len(), versions.len() - 1);
+        assert!(!pruned.contains(&versions[7]));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = AppConfig::default();
+        assert!(config.validate().is_empty());
+
+        config.retention.keep_recent = 0;
+        config.storage = StorageBackend::Sqlite;
+        let paths: Vec<String> = config.validate().into_iter().map(|p| p.path).collect();
+        match cfg!(feature = "sqlite") {
+            true => assert_eq!(paths, vec!["retention.keep_recent"]),
+            false => assert_eq!(paths, vec!["retention.keep_recent", "storage"]),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use ts_rs::TS;
+
+/// What went wrong, as returned by every command. Serializes as `{ kind, message }`, so the
+/// frontend can tell a missing account from a failed simulation without parsing the message.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[serde(tag = "kind", content = "message")]
+#[ts(export, export_to = "../src/rustTypes/", rename = "AppError")]
+pub enum Error {
+    /// A saved item, version or file that doesn't exist.
+    NotFound(String),
+    /// Saved or submitted data that couldn't be parsed.
+    Parse(String),
+    /// Input that parsed but doesn't make sense, such as portfolio weights that don't sum to 1.
+    Validation(String),
+    /// Reading or writing saved data failed, or another call to the OS did.
+    Io(String),
+    /// A simulation, or an analysis built on simulations, couldn't be run.
+    Simulation(String),
+}
+
+impl Error {
+    pub fn message(&self) -> &str {
+        match self {
+            Error::NotFound(m)
+            | Error::Parse(m)
+            | Error::Validation(m)
+            | Error::Io(m)
+            | Error::Simulation(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(e.to_string()),
+            io::ErrorKind::InvalidData => Error::Parse(e.to_string()),
+            io::ErrorKind::InvalidInput => Error::Validation(e.to_string()),
+            _ => Error::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+#[test]
+fn test_error_json() {
+    let e = Error::NotFound("No saved account Checking".to_string());
+    assert_eq!(
+        serde_json::to_value(&e).unwrap(),
+        serde_json::json!({"kind": "NotFound", "message": "No saved account Checking"})
+    );
+    assert_eq!(e.to_string(), "No saved account Checking");
+
+    let missing = io::Error::new(io::ErrorKind::NotFound, "gone");
+    assert_eq!(Error::from(missing), Error::NotFound("gone".to_string()));
+    let denied = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+    assert_eq!(Error::from(denied), Error::Io("denied".to_string()));
+}
@@ -0,0 +1,285 @@
+use super::Transaction;
+use crate::error::Error;
+use crate::sim::validation::{check, Problem};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Which columns of a bank's CSV export hold each part of a transaction. Columns are named by
+/// their header, ignoring case, or by their position counting from 0, for files without headers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct CsvMapping {
+    pub date: String,
+    pub description: String,
+    pub amount: AmountColumns,
+    /// How dates are written, as a chrono format such as `%m/%d/%Y`.
+    pub date_format: String,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default = "default_has_headers")]
+    pub has_headers: bool,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_has_headers() -> bool {
+    true
+}
+
+/// Where a transaction's amount is. `Signed` is a single column that is positive for money in,
+/// or for money out if `negate` is set. `DebitCredit` splits money out and money in across two
+/// columns, one of which is usually blank.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum AmountColumns {
+    Signed {
+        column: String,
+        #[serde(default)]
+        negate: bool,
+    },
+    DebitCredit {
+        debit: String,
+        credit: String,
+    },
+}
+
+/// The transactions read from a CSV file, and the rows that were skipped because they couldn't
+/// be read, such as a balance or totals row at the end of the file.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct CsvImport {
+    pub transactions: Vec<Transaction>,
+    pub skipped: Vec<Problem>,
+}
+
+fn reader(
+    contents: &str,
+    delimiter: char,
+    has_headers: bool,
+) -> Result<::csv::Reader<&[u8]>, Error> {
+    if !delimiter.is_ascii() {
+        return Err(Error::Validation(format!(
+            "Delimiter {:?} must be a single ASCII character",
+            delimiter
+        )));
+    }
+    Ok(::csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .has_headers(has_headers)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(contents.trim_start_matches('\u{feff}').as_bytes()))
+}
+
+fn to_parse_error(e: ::csv::Error) -> Error {
+    Error::Parse(format!("Invalid CSV: {}", e))
+}
+
+/// The headers of a CSV file, to build a mapping from.
+pub fn read_headers(contents: &str, delimiter: char) -> Result<Vec<String>, Error> {
+    let mut reader = reader(contents, delimiter, true)?;
+    let headers = reader.headers().map_err(to_parse_error)?;
+    Ok(headers.iter().map(str::to_string).collect())
+}
+
+/// Parses an amount the way banks write them, such as `1,234.56`, `-$12.00` or `(12.00)`.
+fn parse_amount(s: &str) -> Option<f64> {
+    let (negative, s) = match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, s),
+    };
+    let digits: String = s
+        .chars()
+        .filter(|c| !matches!(c, '$' | '£' | '€' | ',' | ' '))
+        .collect();
+    let amount = digits.parse::<f64>().ok().filter(|a| a.is_finite())?;
+    Some(if negative { -amount } else { amount })
+}
+
+impl CsvMapping {
+    /// Reads every transaction in `contents`. Fails if a mapped column doesn't exist, but rows
+    /// with a missing or unreadable field are skipped and listed in `skipped`, by their position
+    /// among the rows after the headers.
+    pub fn import(&self, contents: &str) -> Result<CsvImport, Error> {
+        let mut reader = reader(contents, self.delimiter, self.has_headers)?;
+        let headers: Vec<String> = match self.has_headers {
+            true => reader
+                .headers()
+                .map_err(to_parse_error)?
+                .iter()
+                .map(str::to_string)
+                .collect(),
+            false => vec![],
+        };
+
+        let mut problems = vec![];
+        let mut column = |field: &str, name: &str| -> usize {
+            let found = headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name.trim()))
+                .or_else(|| name.trim().parse().ok());
+            found.unwrap_or_else(|| {
+                problems.push(Problem::new(field, format!("No column {}", name)));
+                0
+            })
+        };
+        let date = column("date", &self.date);
+        let description = column("description", &self.description);
+        let amount = match &self.amount {
+            AmountColumns::Signed { column: c, negate } => {
+                Amount::Signed(column("amount.column", c), *negate)
+            }
+            AmountColumns::DebitCredit { debit, credit } => Amount::DebitCredit(
+                column("amount.debit", debit),
+                column("amount.credit", credit),
+            ),
+        };
+        check(problems)?;
+
+        let mut transactions = vec![];
+        let mut skipped = vec![];
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(to_parse_error)?;
+            if record.iter().all(str::is_empty) {
+                continue;
+            }
+            let field = |i: usize| record.get(i).unwrap_or("");
+            let transaction = NaiveDate::parse_from_str(field(date), &self.date_format)
+                .map_err(|_| format!("Invalid date {:?}", field(date)))
+                .and_then(|d| {
+                    let amount = amount
+                        .read(&field)
+                        .ok_or(format!("Invalid amount on {}", d))?;
+                    Ok(Transaction {
+                        date: d,
+                        description: field(description).to_string(),
+                        amount,
+                    })
+                });
+            match transaction {
+                Ok(t) => transactions.push(t),
+                Err(message) => skipped.push(Problem::new(&format!("rows[{}]", i), message)),
+            }
+        }
+
+        Ok(CsvImport {
+            transactions,
+            skipped,
+        })
+    }
+}
+
+// The amount columns, resolved to positions
+enum Amount {
+    Signed(usize, bool),
+    DebitCredit(usize, usize),
+}
+
+impl Amount {
+    fn read<'a>(&self, field: &impl Fn(usize) -> &'a str) -> Option<f64> {
+        match *self {
+            Amount::Signed(i, negate) => {
+                parse_amount(field(i)).map(|a| if negate { -a } else { a })
+            }
+            Amount::DebitCredit(debit, credit) => {
+                let (debit, credit) = (field(debit), field(credit));
+                if debit.is_empty() && credit.is_empty() {
+                    return None;
+                }
+                let part = |s: &str| match s {
+                    "" => Some(0.0),
+                    s => parse_amount(s).map(f64::abs),
+                };
+                Some(part(credit)? - part(debit)?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(column: &str) -> CsvMapping {
+        CsvMapping {
+            date: "Date".to_string(),
+            description: "Description".to_string(),
+            amount: AmountColumns::Signed {
+                column: column.to_string(),
+                negate: false,
+            },
+            date_format: "%m/%d/%Y".to_string(),
+            delimiter: ',',
+            has_headers: true,
+        }
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1,234.56"), Some(1234.56));
+        assert_eq!(parse_amount("-$12.00"), Some(-12.0));
+        assert_eq!(parse_amount("(12.00)"), Some(-12.0));
+        assert_eq!(parse_amount("twelve"), None);
+        assert_eq!(parse_amount("NaN"), None);
+    }
+
+    #[test]
+    fn test_import_signed() {
+        let contents = "\u{feff}Date,Description,Amount,Balance\n\
+            01/02/2024,\"ACME, INC PAYROLL\",\"2,500.00\",3000.00\n\
+            01/05/2024,CITY WATER,-45.10,2954.90\n\
+            \n\
+            Ending balance,,,2954.90\n";
+        let import = signed("amount").import(contents).unwrap();
+        assert_eq!(
+            import.transactions,
+            vec![
+                Transaction {
+                    date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+                    description: "ACME, INC PAYROLL".to_string(),
+                    amount: 2500.0,
+                },
+                Transaction {
+                    date: NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(),
+                    description: "CITY WATER".to_string(),
+                    amount: -45.1,
+                },
+            ]
+        );
+        assert_eq!(import.skipped.len(), 1);
+        assert_eq!(import.skipped[0].path, "rows[2]");
+
+        assert_eq!(
+            read_headers(contents, ',').unwrap(),
+            vec!["Date", "Description", "Amount", "Balance"]
+        );
+        assert!(matches!(
+            signed("Withdrawals").import(contents),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_debit_credit() {
+        let mapping = CsvMapping {
+            date: "0".to_string(),
+            description: "1".to_string(),
+            amount: AmountColumns::DebitCredit {
+                debit: "2".to_string(),
+                credit: "3".to_string(),
+            },
+            date_format: "%Y-%m-%d".to_string(),
+            delimiter: ';',
+            has_headers: false,
+        };
+        let contents =
+            "2024-01-02;Payroll;;2500.00\n2024-01-05;Rent;1800.00;\n2024-01-06;Blank;;\n";
+        let import = mapping.import(contents).unwrap();
+        let amounts: Vec<f64> = import.transactions.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![2500.0, -1800.0]);
+        assert_eq!(import.skipped[0].path, "rows[2]");
+    }
+}
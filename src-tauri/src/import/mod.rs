@@ -0,0 +1,17 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub mod csv;
+pub mod ofx;
+pub mod recurring;
+
+/// A single transaction from a bank statement. `amount` is positive for money coming into the
+/// account and negative for money going out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Transaction {
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: f64,
+}
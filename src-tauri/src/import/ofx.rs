@@ -0,0 +1,585 @@
+use super::Transaction;
+use crate::error::Error;
+use crate::sim::cash::Account;
+use crate::sim::portfolio::{Asset, Portfolio};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// The kind of account an OFX statement is for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum OfxAccountKind {
+    Bank,
+    CreditCard,
+    Investment,
+}
+
+/// The kind of security a position is in, from the OFX position aggregate it was listed under.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum SecurityKind {
+    Stock,
+    MutualFund,
+    Debt,
+    StockOption,
+    Other,
+}
+
+impl SecurityKind {
+    /// The annual mean return and standard deviation to assume for a security of this kind.
+    fn assumed_returns(&self) -> (f64, f64) {
+        match self {
+            SecurityKind::Debt => (0.03, 0.05),
+            _ => (0.07, 0.15),
+        }
+    }
+}
+
+/// A holding in an investment account, as of the statement date. `name` is the security's
+/// name from the statement's security list, or its id if it isn't listed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Position {
+    pub security_id: String,
+    pub name: String,
+    pub ticker: Option<String>,
+    pub kind: SecurityKind,
+    pub units: f64,
+    pub unit_price: f64,
+    pub market_value: f64,
+}
+
+/// One account's statement from an OFX file.
+///
+/// `balance` is the ledger balance of a bank or credit card account, or the cash held in an
+/// investment account, not counting its `positions`. `balance_date` is when it was as of.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct OfxStatement {
+    pub kind: OfxAccountKind,
+    pub account_id: String,
+    pub currency: Option<String>,
+    pub balance: Option<f64>,
+    pub balance_date: Option<NaiveDate>,
+    pub transactions: Vec<Transaction>,
+    pub positions: Vec<Position>,
+}
+
+impl OfxStatement {
+    /// The balance plus the market value of every position, including short positions, which
+    /// are worth less than nothing.
+    pub fn total_value(&self) -> f64 {
+        let positions: f64 = self.positions.iter().map(|p| p.market_value).sum();
+        self.balance.unwrap_or(0.0) + positions
+    }
+
+    /// A new account holding the statement's total value from its balance date, or today,
+    /// without any cash flows.
+    pub fn to_account(&self, name: &str, end_date: NaiveDate) -> Account {
+        let start_date = self
+            .balance_date
+            .unwrap_or_else(|| chrono::Local::now().date_naive());
+        Account::new(
+            name.to_string(),
+            self.total_value(),
+            vec![],
+            start_date,
+            end_date,
+        )
+    }
+
+    /// A portfolio with an asset for each position and one for cash, weighted by market value,
+    /// with returns assumed from the kind of each security. `None` if nothing is worth anything.
+    pub fn to_portfolio(&self) -> Option<Portfolio> {
+        // Short positions and overdrawn cash count towards the account's value, but a portfolio
+        // can't hold a negative weight
+        let mut holdings: Vec<(Asset, f64)> = self
+            .positions
+            .iter()
+            .filter(|p| p.market_value > 0.0)
+            .map(|p| {
+                let (mean_return, std_dev) = p.kind.assumed_returns();
+                let name = p.ticker.clone().unwrap_or_else(|| p.name.clone());
+                (Asset::new(name, mean_return, std_dev), p.market_value)
+            })
+            .collect();
+        if let Some(cash) = self.balance.filter(|b| *b > 0.0) {
+            holdings.push((Asset::new("Cash".to_string(), 0.0, 0.0), cash));
+        }
+
+        let total: f64 = holdings.iter().map(|(_, value)| value).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let (assets, values): (Vec<Asset>, Vec<f64>) = holdings.into_iter().unzip();
+        let weights = values.into_iter().map(|v| v / total).collect();
+        Some(Portfolio::new(assets, weights))
+    }
+}
+
+// An element of an OFX document: an aggregate with children, or a leaf with a value
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    value: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.child(name)
+            .map(|c| c.value.as_str())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Every element named `name` under this one, at any depth, in document order.
+    fn find_all(&self, name: &str) -> Vec<&Element> {
+        let mut found = vec![];
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            }
+            found.extend(child.find_all(name));
+        }
+        found
+    }
+}
+
+// The characters Windows-1252 puts in 0x80..=0x9F, where Latin-1 has control characters; the
+// five bytes it leaves undefined are kept as those controls.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// The text of an OFX file. Files declaring a Windows-1252 or Latin-1 character set, in an OFX
+/// 1.x `CHARSET` header or an XML `encoding`, are decoded as Windows-1252; anything else is
+/// read as UTF-8, replacing invalid bytes.
+pub fn text(bytes: &[u8]) -> String {
+    let header = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_ascii_uppercase();
+    let header = header.split("<OFX>").next().unwrap_or_default();
+    let single_byte = ["1252", "ISO-8859-1", "8859-1", "LATIN1"]
+        .iter()
+        .any(|charset| {
+            header
+                .lines()
+                .any(|l| l.trim() == format!("CHARSET:{charset}"))
+                || header.contains(&format!("ENCODING=\"{charset}\""))
+                || header.contains(&format!("ENCODING=\"WINDOWS-{charset}\""))
+        });
+    if !single_byte {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9f => WINDOWS_1252[usize::from(b - 0x80)],
+            _ => char::from(b),
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Parses the body of an OFX document into its `OFX` element.
+///
+/// OFX 2.x is XML, but OFX 1.x is SGML, where elements with a value have no closing tag, so
+/// an element followed by text is taken to be a leaf, and a closing tag closes every element
+/// opened since the one it names.
+fn parse_elements(contents: &str) -> Result<Element, Error> {
+    let start = contents
+        .to_ascii_uppercase()
+        .find("<OFX>")
+        .ok_or(Error::Parse("Not an OFX file".to_string()))?;
+
+    fn close(stack: &mut Vec<Element>) {
+        let done = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(done);
+    }
+
+    let mut stack = vec![Element::default()];
+    let mut rest = &contents[start..];
+    while let Some(open) = rest.find('<') {
+        let end = rest[open..]
+            .find('>')
+            .ok_or(Error::Parse("OFX file has an unclosed tag".to_string()))?
+            + open;
+        let tag = rest[open + 1..end].trim();
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_ascii_uppercase();
+            if let Some(i) = stack.iter().rposition(|e| e.name == name) {
+                while stack.len() > i {
+                    close(&mut stack);
+                }
+            }
+            continue;
+        }
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let text = rest[..rest.find('<').unwrap_or(rest.len())].trim();
+        let element = Element {
+            name: tag.trim_end_matches('/').trim().to_ascii_uppercase(),
+            value: decode(text),
+            children: vec![],
+        };
+        match text.is_empty() && !tag.ends_with('/') {
+            true => stack.push(element),
+            false => stack.last_mut().unwrap().children.push(element),
+        }
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+
+    stack
+        .pop()
+        .unwrap()
+        .children
+        .into_iter()
+        .find(|e| e.name == "OFX")
+        .ok_or(Error::Parse("OFX file has no OFX element".to_string()))
+}
+
+/// Parses an OFX date, such as `20240115` or `20240115120000.000[-5:EST]`.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.get(..8)?, "%Y%m%d").ok()
+}
+
+/// Parses an OFX amount, which may use a comma as the decimal point.
+fn parse_amount(s: &str) -> Option<f64> {
+    s.trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|a| a.is_finite())
+}
+
+fn transaction(e: &Element) -> Result<Transaction, Error> {
+    let id = e.value("FITID").unwrap_or("");
+    let date = e
+        .value("DTPOSTED")
+        .and_then(parse_date)
+        .ok_or(Error::Parse(format!(
+            "Transaction {} has no valid date",
+            id
+        )))?;
+    let amount = e
+        .value("TRNAMT")
+        .and_then(parse_amount)
+        .ok_or(Error::Parse(format!(
+            "Transaction {} has no valid amount",
+            id
+        )))?;
+    let description = e
+        .value("NAME")
+        .or_else(|| e.child("PAYEE").and_then(|p| p.value("NAME")))
+        .or_else(|| e.value("MEMO"))
+        .unwrap_or("");
+    Ok(Transaction {
+        date,
+        description: description.to_string(),
+        amount,
+    })
+}
+
+// Names and tickers of the securities in the file's security list, by id
+type Securities<'a> = HashMap<&'a str, (&'a str, Option<&'a str>)>;
+
+fn securities(ofx: &Element) -> Securities<'_> {
+    ofx.find_all("SECINFO")
+        .into_iter()
+        .filter_map(|info| {
+            let id = info.child("SECID")?.value("UNIQUEID")?;
+            let name = info.value("SECNAME").unwrap_or(id);
+            Some((id, (name, info.value("TICKER"))))
+        })
+        .collect()
+}
+
+fn position(e: &Element, securities: &Securities) -> Result<Position, Error> {
+    let kind = match e.name.as_str() {
+        "POSSTOCK" => SecurityKind::Stock,
+        "POSMF" => SecurityKind::MutualFund,
+        "POSDEBT" => SecurityKind::Debt,
+        "POSOPT" => SecurityKind::StockOption,
+        _ => SecurityKind::Other,
+    };
+    let invpos = e
+        .child("INVPOS")
+        .ok_or(Error::Parse(format!("Position {} has no details", e.name)))?;
+    let security_id = invpos
+        .child("SECID")
+        .and_then(|s| s.value("UNIQUEID"))
+        .ok_or(Error::Parse("Position has no security id".to_string()))?;
+    let number = |field: &str| {
+        invpos
+            .value(field)
+            .and_then(parse_amount)
+            .ok_or(Error::Parse(format!(
+                "Position in {} has no valid {}",
+                security_id, field
+            )))
+    };
+    let units = number("UNITS")?;
+    let unit_price = number("UNITPRICE")?;
+    let market_value = number("MKTVAL").unwrap_or(units * unit_price);
+
+    let (name, ticker) = securities
+        .get(security_id)
+        .copied()
+        .unwrap_or((security_id, None));
+    Ok(Position {
+        security_id: security_id.to_string(),
+        name: name.to_string(),
+        ticker: ticker.map(str::to_string),
+        kind,
+        units,
+        unit_price,
+        market_value,
+    })
+}
+
+fn statement(
+    kind: OfxAccountKind,
+    rs: &Element,
+    securities: &Securities,
+) -> Result<OfxStatement, Error> {
+    let from = match kind {
+        OfxAccountKind::Bank => "BANKACCTFROM",
+        OfxAccountKind::CreditCard => "CCACCTFROM",
+        OfxAccountKind::Investment => "INVACCTFROM",
+    };
+    let (balance, balance_date) = match kind {
+        OfxAccountKind::Investment => (
+            rs.child("INVBAL")
+                .and_then(|b| b.value("AVAILCASH"))
+                .and_then(parse_amount),
+            rs.value("DTASOF").and_then(parse_date),
+        ),
+        _ => {
+            let ledger = rs.child("LEDGERBAL");
+            (
+                ledger
+                    .and_then(|l| l.value("BALAMT"))
+                    .and_then(parse_amount),
+                ledger.and_then(|l| l.value("DTASOF")).and_then(parse_date),
+            )
+        }
+    };
+    let positions = match rs.child("INVPOSLIST") {
+        Some(list) => list
+            .children
+            .iter()
+            .map(|p| position(p, securities))
+            .collect::<Result<Vec<Position>, Error>>()?,
+        None => vec![],
+    };
+
+    Ok(OfxStatement {
+        kind,
+        account_id: rs
+            .child(from)
+            .and_then(|a| a.value("ACCTID"))
+            .unwrap_or("")
+            .to_string(),
+        currency: rs.value("CURDEF").map(str::to_string),
+        balance,
+        balance_date,
+        transactions: rs
+            .find_all("STMTTRN")
+            .into_iter()
+            .map(transaction)
+            .collect::<Result<Vec<Transaction>, Error>>()?,
+        positions,
+    })
+}
+
+/// Reads every bank, credit card and investment statement in an OFX 1.x (SGML) or 2.x (XML)
+/// file, such as a bank's OFX or QFX export.
+pub fn parse(contents: &str) -> Result<Vec<OfxStatement>, Error> {
+    let ofx = parse_elements(contents)?;
+    let securities = securities(&ofx);
+
+    let mut statements = vec![];
+    for (kind, aggregate) in [
+        (OfxAccountKind::Bank, "STMTRS"),
+        (OfxAccountKind::CreditCard, "CCSTMTRS"),
+        (OfxAccountKind::Investment, "INVSTMTRS"),
+    ] {
+        for rs in ofx.find_all(aggregate) {
+            statements.push(statement(kind, rs, &securities)?);
+        }
+    }
+    if statements.is_empty() {
+        return Err(Error::Parse("OFX file has no statements".to_string()));
+    }
+    Ok(statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sgml() {
+        let statements = parse(include_str!("../fixtures/bank_statement_v1.ofx")).unwrap();
+        assert_eq!(statements.len(), 2);
+
+        let checking = &statements[0];
+        assert_eq!(checking.kind, OfxAccountKind::Bank);
+        assert_eq!(checking.account_id, "000123456789");
+        assert_eq!(checking.currency.as_deref(), Some("USD"));
+        assert_eq!(checking.balance, Some(3442.9));
+        assert_eq!(checking.balance_date, Some(date(2024, 1, 31)));
+        assert_eq!(
+            checking.transactions,
+            vec![
+                Transaction {
+                    date: date(2024, 1, 15),
+                    description: "ACME PAYROLL".to_string(),
+                    amount: 2500.0,
+                },
+                Transaction {
+                    date: date(2024, 1, 20),
+                    description: "SMITH & SONS PLUMBING".to_string(),
+                    amount: -45.1,
+                },
+                Transaction {
+                    date: date(2024, 1, 22),
+                    description: "SERVICE FEE".to_string(),
+                    amount: -12.0,
+                },
+            ]
+        );
+        assert!(checking.positions.is_empty());
+        assert!(checking.to_portfolio().is_some());
+
+        let card = &statements[1];
+        assert_eq!(card.kind, OfxAccountKind::CreditCard);
+        assert_eq!(card.balance, Some(-15.99));
+        assert_eq!(card.transactions[0].description, "NETFLIX.COM");
+        assert!(card.to_portfolio().is_none());
+    }
+
+    #[test]
+    fn test_parse_xml() {
+        let statements = parse(include_str!("../fixtures/investment_statement_v2.ofx")).unwrap();
+        assert_eq!(statements.len(), 1);
+
+        let brokerage = &statements[0];
+        assert_eq!(brokerage.kind, OfxAccountKind::Investment);
+        assert_eq!(brokerage.account_id, "Z12345678");
+        assert_eq!(brokerage.balance, Some(10000.0));
+        assert_eq!(brokerage.balance_date, Some(date(2024, 3, 29)));
+        assert_eq!(brokerage.transactions.len(), 1);
+        assert_eq!(brokerage.transactions[0].amount, 500.0);
+
+        let fund = &brokerage.positions[0];
+        assert_eq!(fund.kind, SecurityKind::MutualFund);
+        assert_eq!(fund.name, "Total Stock Market Index Fund");
+        assert_eq!(fund.ticker.as_deref(), Some("VTSAX"));
+        assert_eq!(fund.market_value, 25000.0);
+        let note = &brokerage.positions[1];
+        assert_eq!(note.kind, SecurityKind::Debt);
+        assert_eq!(note.ticker, None);
+
+        // Seeds an account with the cash and positions, and a portfolio weighted by value
+        let account = brokerage.to_account("Brokerage", date(2050, 12, 31));
+        assert_eq!(account.balance, 50000.0);
+        assert_eq!(account.start_date, date(2024, 3, 29));
+        assert!(account.validate().is_empty());
+
+        let portfolio = brokerage.to_portfolio().unwrap();
+        let names: Vec<&str> = portfolio.assets.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["VTSAX", "US Treasury Note", "Cash"]);
+        assert_eq!(portfolio.weights, vec![0.5, 0.3, 0.2]);
+        assert_eq!(portfolio.assets[1].mean_return, 0.03);
+        assert!(portfolio.validate().is_empty());
+    }
+
+    #[test]
+    fn test_text() {
+        let mut v1 = b"OFXHEADER:100\r\nCHARSET:1252\r\n\r\n<OFX><NAME>CAF\xc9 \x80".to_vec();
+        assert_eq!(
+            text(&v1),
+            "OFXHEADER:100\r\nCHARSET:1252\r\n\r\n<OFX><NAME>CAF\u{c9} \u{20ac}"
+        );
+        let v2 = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?><OFX>\x92";
+        assert!(text(v2).ends_with("<OFX>\u{2019}"));
+
+        // UTF-8 stays UTF-8, and bytes that aren't are replaced rather than failing
+        assert_eq!(
+            text("CHARSET:NONE\n<OFX>CAF\u{c9}".as_bytes()),
+            "CHARSET:NONE\n<OFX>CAF\u{c9}"
+        );
+        v1[15..27].copy_from_slice(b"CHARSET:NONE");
+        assert!(text(&v1).ends_with("CAF\u{fffd} \u{fffd}"));
+
+        // A 1.x statement exported in Windows-1252 parses through to its descriptions
+        let statement = include_str!("../fixtures/bank_statement_v1.ofx")
+            .replace("ACME PAYROLL", "CAF\u{c9} PAYROLL");
+        let bytes: Vec<u8> = statement
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).unwrap())
+            .collect();
+        let statements = parse(&text(&bytes)).unwrap();
+        assert_eq!(
+            statements[0].transactions[0].description,
+            "CAF\u{c9} PAYROLL"
+        );
+    }
+
+    #[test]
+    fn test_short_positions() {
+        let mut brokerage = parse(include_str!("../fixtures/investment_statement_v2.ofx"))
+            .unwrap()
+            .remove(0);
+        brokerage.positions[1].market_value = -15000.0;
+
+        // Short positions lower the account's value, but are left out of the portfolio
+        let account = brokerage.to_account("Brokerage", date(2050, 12, 31));
+        assert_eq!(account.balance, 20000.0);
+        let portfolio = brokerage.to_portfolio().unwrap();
+        let names: Vec<&str> = portfolio.assets.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["VTSAX", "Cash"]);
+        assert_eq!(portfolio.weights[0] * 35000.0, 25000.0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            parse("Date,Description,Amount\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse("<OFX><SIGNONMSGSRSV1></SIGNONMSGSRSV1></OFX>"),
+            Err(Error::Parse(_))
+        ));
+        let bad_amount = "<OFX><STMTRS><BANKTRANLIST><STMTTRN><DTPOSTED>20240101\
+            <TRNAMT>lots</STMTTRN></BANKTRANLIST></STMTRS></OFX>";
+        assert!(matches!(parse(bad_amount), Err(Error::Parse(_))));
+    }
+}
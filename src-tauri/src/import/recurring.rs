@@ -0,0 +1,318 @@
+use super::Transaction;
+use crate::sim::cash::{CashFlow, Frequency};
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+// Fewer payments than this aren't enough to call something recurring, except annual ones
+const MIN_OCCURRENCES: usize = 3;
+// The share of gaps between payments that must fit the cadence
+const MIN_REGULARITY: f64 = 0.75;
+
+/// How often a payee was paid, or paid the account, in a run of transactions.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum Cadence {
+    BusinessDaily,
+    Weekly,
+    Biweekly,
+    SemiMonthly,
+    Monthly,
+    Annually,
+}
+
+impl Cadence {
+    /// The cadence with a typical gap of `days` between payments. Biweekly payments have the
+    /// same gap as semi-monthly ones, and are told apart by `refine`.
+    fn from_gap(days: i64) -> Option<Cadence> {
+        match days {
+            1..=4 => Some(Cadence::BusinessDaily),
+            6..=8 => Some(Cadence::Weekly),
+            12..=18 => Some(Cadence::SemiMonthly),
+            26..=35 => Some(Cadence::Monthly),
+            350..=380 => Some(Cadence::Annually),
+            _ => None,
+        }
+    }
+
+    /// Semi-monthly payments fall on the same two days of every month, so every other payment is
+    /// a month apart, where biweekly ones are exactly four weeks apart and drift through the
+    /// month.
+    fn refine(self, dates: &[NaiveDate]) -> Cadence {
+        if self != Cadence::SemiMonthly || dates.len() < 3 {
+            return self;
+        }
+        let every_other: Vec<i64> = dates.windows(3).map(|w| (w[2] - w[0]).num_days()).collect();
+        match median(&every_other) <= 28 {
+            true => Cadence::Biweekly,
+            false => Cadence::SemiMonthly,
+        }
+    }
+
+    /// Whether a gap of `days` between two payments fits the cadence, allowing for weekends,
+    /// holidays and months of different lengths.
+    fn fits(&self, days: i64) -> bool {
+        match self {
+            Cadence::BusinessDaily => (1..=4).contains(&days),
+            Cadence::Weekly => (6..=8).contains(&days),
+            Cadence::Biweekly => (13..=15).contains(&days),
+            Cadence::SemiMonthly => (12..=18).contains(&days),
+            Cadence::Monthly => (26..=35).contains(&days),
+            Cadence::Annually => (350..=380).contains(&days),
+        }
+    }
+
+    fn per_year(&self) -> f64 {
+        match self {
+            Cadence::BusinessDaily => 252.0,
+            Cadence::Weekly => 52.0,
+            Cadence::Biweekly => 26.0,
+            Cadence::SemiMonthly => 24.0,
+            Cadence::Monthly => 12.0,
+            Cadence::Annually => 1.0,
+        }
+    }
+
+    /// The closest cash flow frequency. Weekly and biweekly payments become semi-monthly ones.
+    fn frequency(&self, days_of_month: &[u32]) -> Frequency {
+        match self {
+            Cadence::BusinessDaily => Frequency::BusinesDay,
+            Cadence::Weekly | Cadence::Biweekly | Cadence::SemiMonthly => Frequency::SemiMonthly,
+            Cadence::Monthly if median(days_of_month) <= 15 => Frequency::MonthStart,
+            Cadence::Monthly => Frequency::MonthEnd,
+            Cadence::Annually => Frequency::Annually,
+        }
+    }
+}
+
+/// A payee that was paid, or paid the account, on a regular cadence, with a proposed cash flow
+/// to continue it.
+///
+/// The cash flow's amount is annual, like every cash flow's: the typical (median) payment times
+/// the number of payments a year at the cadence. A year of it adds up to the same total where
+/// the frequency is coarser than the cadence, such as a weekly payment becoming a semi-monthly
+/// one. `regularity` is the share of the gaps between payments that fit the cadence, from 0
+/// to 1.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct RecurringFlow {
+    pub payee: String,
+    pub cadence: Cadence,
+    pub occurrences: usize,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    pub regularity: f64,
+    pub cash_flow: CashFlow,
+}
+
+fn median<T: Copy + Ord>(values: &[T]) -> T {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    sorted[sorted.len() / 2]
+}
+
+fn median_amount(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+/// The payee in a transaction description, without the reference numbers, dates and card
+/// numbers that differ between payments to the same payee, such as `ACME PAYROLL` from
+/// `ACME PAYROLL 0423 #99123`.
+pub fn payee(description: &str) -> String {
+    let words: Vec<String> = description
+        .split(|c: char| c.is_whitespace() || matches!(c, '*' | '#' | '/' | ',' | ':'))
+        .filter(|w| !w.is_empty() && w.chars().all(|c| c.is_alphabetic() || "&'.-".contains(c)))
+        .take(3)
+        .map(str::to_uppercase)
+        .collect();
+    match words.is_empty() {
+        true => description.trim().to_uppercase(),
+        false => words.join(" "),
+    }
+}
+
+/// Groups transactions by payee and direction, and proposes a cash flow for each group that
+/// recurs on a regular cadence, largest yearly total first.
+pub fn detect_recurring(transactions: &[Transaction]) -> Vec<RecurringFlow> {
+    // Payments on the same day to the same payee count as one
+    let mut groups: BTreeMap<(String, bool), BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    for t in transactions.iter().filter(|t| t.amount != 0.0) {
+        *groups
+            .entry((payee(&t.description), t.amount > 0.0))
+            .or_default()
+            .entry(t.date)
+            .or_default() += t.amount;
+    }
+
+    let mut flows: Vec<RecurringFlow> = groups
+        .into_iter()
+        .filter_map(|((payee, _), payments)| {
+            let dates: Vec<NaiveDate> = payments.keys().copied().collect();
+            let amounts: Vec<f64> = payments.values().copied().collect();
+            let gaps: Vec<i64> = dates.windows(2).map(|w| (w[1] - w[0]).num_days()).collect();
+            if gaps.is_empty() {
+                return None;
+            }
+
+            let cadence = Cadence::from_gap(median(&gaps))?.refine(&dates);
+            let needed = match cadence {
+                Cadence::Annually => 2,
+                _ => MIN_OCCURRENCES,
+            };
+            let regularity =
+                gaps.iter().filter(|g| cadence.fits(**g)).count() as f64 / gaps.len() as f64;
+            if dates.len() < needed || regularity < MIN_REGULARITY {
+                return None;
+            }
+
+            let days: Vec<u32> = dates.iter().map(|d| d.day()).collect();
+            let frequency = cadence.frequency(&days);
+            let amount = median_amount(&amounts) * cadence.per_year();
+            let last_date = *dates.last().unwrap();
+            // Annual flows need a date to recur on, so they start on the next anniversary
+            let start_date = match frequency {
+                Frequency::Annually => last_date.checked_add_months(Months::new(12)),
+                _ => None,
+            };
+            Some(RecurringFlow {
+                cash_flow: CashFlow::new(
+                    Some(payee.clone()),
+                    amount,
+                    Some(frequency),
+                    start_date,
+                    None,
+                    None,
+                    None,
+                ),
+                payee,
+                cadence,
+                occurrences: dates.len(),
+                first_date: dates[0],
+                last_date,
+                regularity,
+            })
+        })
+        .collect();
+
+    flows.sort_by(|a, b| {
+        b.cash_flow
+            .amount
+            .abs()
+            .total_cmp(&a.cash_flow.amount.abs())
+    });
+    flows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn transaction(date: NaiveDate, description: &str, amount: f64) -> Transaction {
+        Transaction {
+            date,
+            description: description.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_payee() {
+        assert_eq!(payee("ACME PAYROLL 0423 PPD ID: 99123"), "ACME PAYROLL PPD");
+        assert_eq!(payee("Netflix.com 866-579-7172 CA"), "NETFLIX.COM CA");
+        assert_eq!(payee("SQ *BLUE BOTTLE #123"), "SQ BLUE BOTTLE");
+        assert_eq!(payee("12345"), "12345");
+    }
+
+    #[test]
+    fn test_detect_recurring() {
+        let mut transactions = vec![];
+        for month in 1..=6 {
+            // Rent on the 1st, paid a day late in March
+            let day = if month == 3 { 2 } else { 1 };
+            transactions.push(transaction(date(2024, month, day), "RENT 00123", -1800.0));
+            // Pay on the 15th and the last day of the month
+            transactions.push(transaction(date(2024, month, 15), "ACME PAYROLL", 2500.0));
+            let last = date(2024, month + 1, 1).pred_opt().unwrap();
+            transactions.push(transaction(last, "ACME PAYROLL", 2500.0));
+            // A utility bill that varies month to month
+            let bill = -50.0 - month as f64 * 5.0;
+            transactions.push(transaction(date(2024, month, 20), "CITY WATER", bill));
+        }
+        // Weekly groceries
+        for week in 0..20 {
+            let d = date(2024, 1, 6) + chrono::Duration::weeks(week);
+            transactions.push(transaction(d, "GROCER #12", -100.0));
+        }
+        transactions.push(transaction(date(2023, 3, 10), "INSURANCE CO", -900.0));
+        transactions.push(transaction(date(2024, 3, 11), "INSURANCE CO", -900.0));
+        // One-off and irregular spending isn't recurring
+        transactions.push(transaction(date(2024, 2, 3), "HARDWARE STORE", -45.0));
+        for (m, d) in [(1, 3), (1, 9), (3, 28), (6, 2)] {
+            transactions.push(transaction(date(2024, m, d), "BOOKSHOP", -20.0));
+        }
+
+        let flows = detect_recurring(&transactions);
+        let payees: Vec<&str> = flows.iter().map(|f| f.payee.as_str()).collect();
+        assert_eq!(
+            payees,
+            vec![
+                "ACME PAYROLL",
+                "RENT",
+                "GROCER",
+                "INSURANCE CO",
+                "CITY WATER"
+            ]
+        );
+
+        let pay = &flows[0];
+        assert_eq!(pay.cadence, Cadence::SemiMonthly);
+        assert!(matches!(pay.cash_flow.frequency, Frequency::SemiMonthly));
+        assert_eq!(pay.cash_flow.amount, 2500.0 * 24.0);
+        assert_eq!(pay.occurrences, 12);
+
+        let rent = &flows[1];
+        assert!(matches!(rent.cash_flow.frequency, Frequency::MonthStart));
+        assert_eq!(rent.cash_flow.amount, -21600.0);
+        assert_eq!(rent.regularity, 1.0);
+
+        // 52 weekly payments a year are paid out as 24 semi-monthly ones of the same total
+        let groceries = &flows[2];
+        assert_eq!(groceries.cadence, Cadence::Weekly);
+        assert!(matches!(
+            groceries.cash_flow.frequency,
+            Frequency::SemiMonthly
+        ));
+        assert_eq!(groceries.cash_flow.amount, -100.0 * 52.0);
+
+        let insurance = &flows[3];
+        assert_eq!(insurance.cadence, Cadence::Annually);
+        assert_eq!(insurance.cash_flow.start_date, Some(date(2025, 3, 11)));
+        assert!(insurance.cash_flow.validate().is_empty());
+
+        let water = &flows[4];
+        assert!(matches!(water.cash_flow.frequency, Frequency::MonthEnd));
+        assert_eq!(water.cash_flow.amount, -65.0 * 12.0);
+    }
+
+    #[test]
+    fn test_detect_biweekly() {
+        // Paid every other Friday, drifting through the month
+        let transactions: Vec<Transaction> = (0..12)
+            .map(|i| {
+                let d = date(2024, 1, 5) + chrono::Duration::weeks(2 * i);
+                transaction(d, "ACME PAYROLL", 2000.0)
+            })
+            .collect();
+        let flows = detect_recurring(&transactions);
+        assert_eq!(flows[0].cadence, Cadence::Biweekly);
+        assert_eq!(flows[0].cash_flow.amount, 2000.0 * 26.0);
+        assert_eq!(flows[0].regularity, 1.0);
+    }
+}
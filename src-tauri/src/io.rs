@@ -1,46 +1,339 @@
+use crate::config::{AppConfig, RetentionPolicy, StorageBackend};
+use crate::error::Error;
 use crate::sim::cash::Account;
-use std::ffi::OsStr;
+use crate::sim::compare::AccountDiff;
+use crate::sim::historical::ReturnsDataset;
+use crate::sim::portfolio::Portfolio;
+use crate::sim::validation::check;
+use crate::sim::Scenario;
+use crate::storage::fs::FsStorage;
+#[cfg(feature = "sqlite")]
+use crate::storage::sqlite::SqliteStorage;
+use crate::storage::Storage;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
 use std::fs;
-use std::io;
-use serde::de::Error;
-// TODO: replace with Tauri's FS or another store
-use serde_yaml;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use ts_rs::TS;
+use uuid::Uuid;
 
-fn save_dir() -> PathBuf {
+/// Environment variable that overrides the data dir.
+pub const DATA_DIR_ENV: &str = "TORTOISE_DATA_DIR";
+
+// A file with this name next to the executable turns on portable mode
+const PORTABLE_MARKER: &str = "portable";
+
+static PORTABLE: OnceLock<bool> = OnceLock::new();
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+fn default_dir() -> Result<PathBuf, Error> {
     dirs::config_dir()
-        .expect("No config dir")
-        .join("The Tortoise")
+        .map(|d| d.join("The Tortoise"))
+        .ok_or(Error::Io(
+            "Could not find the user's config dir".to_string(),
+        ))
 }
 
-pub fn get_or_create_save_dir() -> PathBuf {
-    let dir = save_dir();
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(|d| d.to_path_buf())
+}
 
-    if dir.exists() {
-        dir
-    } else {
-        std::fs::create_dir_all(&dir).expect("Could not create save dir");
-        dir
+fn is_portable() -> bool {
+    *PORTABLE.get_or_init(|| exe_dir().is_some_and(|d| d.join(PORTABLE_MARKER).exists()))
+}
+
+// The app config lives here, and so does the data unless it has been moved. In portable mode,
+// that's a `data` folder next to the executable.
+fn config_dir() -> Result<PathBuf, Error> {
+    if let Some(dir) = CONFIG_DIR.get() {
+        return Ok(dir.clone());
     }
+    let dir = match exe_dir().filter(|_| is_portable()) {
+        Some(dir) => dir.join("data"),
+        None => default_dir()?,
+    };
+    Ok(CONFIG_DIR.get_or_init(|| dir).clone())
+}
+
+/// Keeps the app config in `dir` instead of the user's config dir. Like `init_data_dir`, this
+/// has to happen before anything is read or written.
+pub fn init_config_dir(dir: PathBuf) -> Result<(), String> {
+    CONFIG_DIR
+        .set(dir)
+        .map_err(|_| "The config dir is already set".to_string())
+}
+
+/// Points the app config and data dir at a temp dir of this process, and the store at memory,
+/// so that unit tests never read or change the user's data. Every test that goes through this
+/// module calls it first.
+#[cfg(test)]
+pub(crate) fn init_test_dirs() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let dir = std::env::temp_dir().join(format!("tortoise-test-{}", std::process::id()));
+        init_config_dir(dir.clone()).unwrap();
+        init_data_dir(Some(dir.join("data")), false).unwrap();
+        init_storage(Box::new(crate::storage::memory::MemoryStorage::new())).unwrap();
+    });
+}
+
+fn env_data_dir() -> Option<PathBuf> {
+    std::env::var_os(DATA_DIR_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Picks the data dir from, in order: the command line flag, the environment variable, the app
+/// config, and the config dir.
+pub fn resolve_data_dir(
+    cli_data_dir: Option<PathBuf>,
+    env_data_dir: Option<PathBuf>,
+    config: &AppConfig,
+) -> Result<PathBuf, Error> {
+    match cli_data_dir.or(env_data_dir).or(config.data_dir.clone()) {
+        Some(dir) => Ok(dir),
+        None => config_dir(),
+    }
+}
+
+/// Applies the `--data-dir` and `--portable` command line flags. Like `init_storage`, this has
+/// to happen before anything is read or written.
+pub fn init_data_dir(cli_data_dir: Option<PathBuf>, portable: bool) -> Result<(), String> {
+    if portable {
+        PORTABLE
+            .set(true)
+            .map_err(|_| "Portable mode is already set".to_string())?;
+    }
+    let dir = resolve_data_dir(cli_data_dir, env_data_dir(), &read_app_config())
+        .map_err(|e| e.to_string())?;
+    DATA_DIR
+        .set(dir)
+        .map_err(|_| "The data dir is already set".to_string())
+}
+
+fn save_dir() -> Result<PathBuf, Error> {
+    if let Some(dir) = DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let dir = resolve_data_dir(None, env_data_dir(), &read_app_config())?;
+    Ok(DATA_DIR.get_or_init(|| dir).clone())
+}
+
+pub fn get_or_create_save_dir() -> Result<PathBuf, Error> {
+    let dir = save_dir()?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| Error::Io(format!("Could not create {}: {}", dir.display(), e)))?;
+    Ok(dir)
 }
 
 #[test]
-fn test_get_save_dir() {
-    let dir = get_or_create_accounts_save_dir();
-    assert!(dir.is_dir());
+fn test_resolve_data_dir() {
+    init_test_dirs();
+    let mut config = AppConfig::default();
+    assert_eq!(
+        resolve_data_dir(None, None, &config).unwrap(),
+        config_dir().unwrap()
+    );
+
+    config.data_dir = Some(PathBuf::from("/settings"));
+    assert_eq!(
+        resolve_data_dir(None, None, &config).unwrap(),
+        PathBuf::from("/settings")
+    );
+    let env = Some(PathBuf::from("/env"));
+    assert_eq!(
+        resolve_data_dir(None, env.clone(), &config).unwrap(),
+        PathBuf::from("/env")
+    );
+    let cli = Some(PathBuf::from("/cli"));
+    assert_eq!(
+        resolve_data_dir(cli, env, &config).unwrap(),
+        PathBuf::from("/cli")
+    );
 }
 
-// ACCOUNTS
+// Copies the files under `from` into `to`, except `skip` and any that already exist there, and
+// returns how many were copied
+fn copy_dir(from: &Path, to: &Path, skip: &Path) -> Result<usize, Error> {
+    fs::create_dir_all(to)?;
+    let mut copied = 0;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copied += copy_dir(&path, &target, skip)?;
+        } else if path != skip && !target.exists() {
+            fs::copy(&path, &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
 
-pub fn get_or_create_accounts_save_dir() -> PathBuf {
-    let dir = get_or_create_save_dir().join("accounts");
+/// Copies the saved data to `to` and makes it the data dir from the next start, returning the
+/// number of files copied. Files already in `to` are kept, and the old data is left in place.
+/// The new data dir is saved as an absolute path, and can't be inside the current one or
+/// contain it.
+pub fn migrate_data_dir(to: &Path) -> Result<usize, Error> {
+    let config_path = app_config_path()?;
+    let skip = config_path.canonicalize().unwrap_or(config_path);
+    let (to, copied) = copy_data_dir(&save_dir()?, to, &skip)?;
 
-    if dir.exists() {
-        dir
+    let mut config = read_app_config();
+    config.data_dir = Some(to);
+    write_app_config(&config)?;
+    Ok(copied)
+}
+
+// Copies `from` into `to` after resolving both to absolute paths, returning the resolved `to`
+// and the number of files copied. `to` is created if it doesn't exist, and removed again if it
+// can't be used.
+fn copy_data_dir(from: &Path, to: &Path, skip: &Path) -> Result<(PathBuf, usize), Error> {
+    let created = !to.exists();
+    fs::create_dir_all(to)?;
+    let to = to.canonicalize()?;
+    let from = from.canonicalize().unwrap_or(from.to_path_buf());
+    let problem = if to == from {
+        Some("That is already the data dir")
+    } else if to.starts_with(&from) {
+        Some("The new data dir can't be inside the current one")
+    } else if from.starts_with(&to) {
+        Some("The new data dir can't contain the current one")
     } else {
-        std::fs::create_dir_all(&dir).expect("Could not create save dir");
-        dir
+        None
+    };
+    if let Some(problem) = problem {
+        if created {
+            let _ = fs::remove_dir(&to);
+        }
+        return Err(Error::Validation(problem.to_string()));
     }
+
+    // The app config stays where it is
+    let copied = match from.is_dir() {
+        true => copy_dir(&from, &to, skip)?,
+        false => 0,
+    };
+    Ok((to, copied))
+}
+
+#[test]
+fn test_copy_dir() {
+    let root = std::env::temp_dir().join(format!("tortoise-copy-{}", std::process::id()));
+    let (from, to) = (root.join("from"), root.join("to"));
+    fs::create_dir_all(from.join("accounts").join("Checking")).unwrap();
+    fs::write(from.join("accounts").join("Checking").join("a.yaml"), "a").unwrap();
+    fs::write(from.join("tortoise.db"), "b").unwrap();
+    fs::create_dir_all(&to).unwrap();
+    fs::write(to.join("tortoise.db"), "c").unwrap();
+    fs::write(from.join("config.yaml"), "d").unwrap();
+
+    assert_eq!(copy_dir(&from, &to, &from.join("config.yaml")).unwrap(), 1);
+    assert!(!to.join("config.yaml").exists());
+    let copied = to.join("accounts").join("Checking").join("a.yaml");
+    assert_eq!(fs::read_to_string(copied).unwrap(), "a");
+    assert_eq!(fs::read_to_string(to.join("tortoise.db")).unwrap(), "c");
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_copy_data_dir() {
+    let root = std::env::temp_dir().join(format!("tortoise-move-{}", std::process::id()));
+    let from = root.join("from");
+    fs::create_dir_all(&from).unwrap();
+    fs::write(from.join("a.yaml"), "a").unwrap();
+    let skip = from.join("config.yaml");
+
+    // Relative paths are resolved, and nesting either way is refused
+    let same = from.join("..").join("from");
+    assert!(copy_data_dir(&from, &same, &skip).is_err());
+    let inside = from.join("backup");
+    assert!(copy_data_dir(&from, &inside, &skip).is_err());
+    assert!(!inside.exists());
+    assert!(copy_data_dir(&from, &root, &skip).is_err());
+
+    let (to, copied) = copy_data_dir(&from, &root.join("to"), &skip).unwrap();
+    assert!(to.is_absolute());
+    assert_eq!(to, root.join("to").canonicalize().unwrap());
+    assert_eq!(copied, 1);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+// APP CONFIG
+
+fn app_config_path() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join("config.yaml"))
+}
+
+/// Reads the app config, falling back to the defaults if it hasn't been saved or can't be read.
+pub fn read_app_config() -> AppConfig {
+    app_config_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|s| serde_yaml::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_app_config(config: &AppConfig) -> Result<(), Error> {
+    check(config.validate())?;
+    fs::create_dir_all(config_dir()?)?;
+    fs::write(app_config_path()?, to_yaml(config)?)?;
+    Ok(())
+}
+
+// STORAGE
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+/// Sets the storage backend for the rest of the session. This has to happen before anything is
+/// read or written, as the first access otherwise settles on the default backend.
+pub fn init_storage(storage: Box<dyn Storage>) -> Result<(), String> {
+    STORAGE
+        .set(storage)
+        .map_err(|_| "Storage is already initialized".to_string())
+}
+
+/// The storage backend selected in the app config, in the data dir. Fails if the SQLite store
+/// can't be opened, or this build has no SQLite support, rather than saving elsewhere.
+pub fn storage_from_config(config: &AppConfig) -> Result<Box<dyn Storage>, Error> {
+    match config.storage {
+        StorageBackend::Filesystem => Ok(Box::new(FsStorage::new(get_or_create_save_dir()?))),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => {
+            let path = get_or_create_save_dir()?.join("tortoise.db");
+            let storage = SqliteStorage::open(&path).map_err(|e| {
+                Error::Io(format!(
+                    "Could not open the SQLite store {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            Ok(Box::new(storage))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => Err(Error::Validation(
+            "This build has no SQLite support".to_string(),
+        )),
+    }
+}
+
+// The store set with `init_storage` at startup, or else the one in the app config, opened on
+// first use
+fn storage() -> Result<&'static dyn Storage, Error> {
+    if let Some(storage) = STORAGE.get() {
+        return Ok(storage.as_ref());
+    }
+    let storage = storage_from_config(&read_app_config())?;
+    Ok(STORAGE.get_or_init(|| storage).as_ref())
 }
 
 /// Generates a timestamp-based version string.
@@ -54,123 +347,897 @@ fn get_next_version() -> String {
     version
 }
 
-pub fn write_account_file(account: &Account) {
-    // TODO: This results in a lot of config files being created. We should probably
-    // cull older files so that the X most recent are kept, followed by Y daily, and
-    // Z monthly, etc.
-    let dir = get_or_create_accounts_save_dir();
-    let fsn = &account.fs_name().clone();
-
-    let account_folder = dir.join(fsn);
-    std::fs::create_dir_all(&account_folder).expect("Could not create account folder");
-
-    let v = get_next_version();
-    let account_filename = format!("{}_{}.yaml", fsn, v);
-
-    let account_str = serde_yaml::to_string(&account).expect("Could not serialize account");
-    let account_path = account_folder.join(account_filename);
-
-    std::fs::write(account_path, account_str).expect("Could not write account file");
-}
-
-pub fn list_accounts() -> Vec<String> {
-    let dir = get_or_create_accounts_save_dir();
-    let mut account_names = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(&dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                if entry.file_type().unwrap().is_dir() {
-                    if let Some(account_name) = entry.file_name().to_str() {
-                        let configs = entry.path().read_dir().unwrap().filter_map(|entry| {
-                            if entry.unwrap().path().extension() == Some(&OsStr::new("yaml")) {
-                                Some(true)
-                            } else {
-                                None
-                            }
-                        });
-                        if configs.count() > 0 {
-                            account_names.push(account_name.to_string());
-                        }
-                    }
-                }
+const VERSION_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// A saved version of an item in a versioned store, and when it was saved.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Version {
+    pub version: String,
+    pub saved_at: chrono::NaiveDateTime,
+}
+
+// VERSIONED STORE
+//
+// Each item in a collection is saved as YAML, with a new timestamped version per save. The
+// latest version is the one read back, and deleting an item moves all of its versions to a
+// timestamped tombstone.
+
+const ACCOUNTS: &str = "accounts";
+const SCENARIOS: &str = "scenarios";
+const PORTFOLIOS: &str = "portfolios";
+const RETURNS: &str = "returns";
+
+fn to_yaml<T: Serialize>(value: &T) -> Result<String, Error> {
+    Ok(serde_yaml::to_string(value)?)
+}
+
+// SCHEMA VERSIONS
+//
+// Saved items are stamped with a `schema_version`, and items saved by older versions of the app
+// are upgraded on read, one version at a time, before they are parsed. Items saved before
+// versioning have no `schema_version`, and are version 0. Changing a saved type means adding a
+// step to its collection's migrations.
+
+const SCHEMA_VERSION: &str = "schema_version";
+
+/// Upgrades a saved item from one schema version to the next.
+type Migration = fn(&mut Mapping);
+
+// Step `i` upgrades an item from version `i`, so items are saved at the number of steps
+const ACCOUNT_MIGRATIONS: &[Migration] = &[account_v1];
+const SCENARIO_MIGRATIONS: &[Migration] = &[scenario_v1];
+const PORTFOLIO_MIGRATIONS: &[Migration] = &[unchanged];
+
+fn migrations(collection: &str) -> &'static [Migration] {
+    match collection {
+        ACCOUNTS => ACCOUNT_MIGRATIONS,
+        SCENARIOS => SCENARIO_MIGRATIONS,
+        PORTFOLIOS => PORTFOLIO_MIGRATIONS,
+        _ => &[],
+    }
+}
+
+/// The schema version items in `collection` are saved at.
+fn schema_version(collection: &str) -> usize {
+    migrations(collection).len()
+}
+
+fn insert_missing(item: &mut Mapping, key: &str, value: Value) {
+    if !item.contains_key(key) {
+        item.insert(key.into(), value);
+    }
+}
+
+// Version 1 spells out the fields that were added with defaults before versioning: an account's
+// id, interest and owner, and its cash flows' start and end events
+fn account_v1(account: &mut Mapping) {
+    insert_missing(account, "id", Uuid::new_v4().to_string().into());
+    insert_missing(account, "interest", Value::Null);
+    insert_missing(account, "owner", Value::Null);
+    if let Some(Value::Sequence(cash_flows)) = account.get_mut("cash_flows") {
+        for cash_flow in cash_flows.iter_mut().filter_map(Value::as_mapping_mut) {
+            insert_missing(cash_flow, "start_event", Value::Null);
+            insert_missing(cash_flow, "end_event", Value::Null);
+        }
+    }
+}
+
+// Version 1 upgrades the scenario's accounts
+fn scenario_v1(scenario: &mut Mapping) {
+    if let Some(Value::Sequence(accounts)) = scenario.get_mut("accounts") {
+        for invested in accounts.iter_mut().filter_map(Value::as_mapping_mut) {
+            if let Some(Value::Mapping(account)) = invested.get_mut("account") {
+                account_v1(account);
             }
         }
     }
-    account_names
 }
 
-pub fn list_accounts_detail() -> Vec<Account> {
-    let accounts: Vec<String> = list_accounts();
-    let mut account_details = Vec::new();
-    for account in accounts.iter() {
-        account_details.push(read_account(&account).unwrap());
+// Only stamps the version
+fn unchanged(_: &mut Mapping) {}
+
+/// Serializes an item in `collection`, stamped with the schema version it is saved at.
+fn to_document<T: Serialize>(collection: &str, value: &T) -> Result<String, Error> {
+    let Value::Mapping(fields) = serde_yaml::to_value(value)? else {
+        return Err(Error::Parse(format!(
+            "Items in {} must be saved as a mapping",
+            collection
+        )));
+    };
+    let mut document = Mapping::new();
+    document.insert(SCHEMA_VERSION.into(), schema_version(collection).into());
+    document.extend(fields);
+    to_yaml(&document)
+}
+
+/// Parses an item in `collection`, first upgrading it from the schema version it was saved at.
+fn from_document<T: DeserializeOwned>(collection: &str, contents: &str) -> Result<T, Error> {
+    let mut document: Mapping = serde_yaml::from_str(contents)?;
+    let version = match document.remove(SCHEMA_VERSION) {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .ok_or(Error::Parse(format!("Invalid schema version {:?}", v)))?
+            as usize,
+    };
+    let steps = migrations(collection);
+    if version > steps.len() {
+        return Err(Error::Parse(format!(
+            "Saved with schema version {}, but this version of the app only reads up to {}",
+            version,
+            steps.len()
+        )));
     }
-    account_details
+    for step in &steps[version..] {
+        step(&mut document);
+    }
+    Ok(serde_yaml::from_value(Value::Mapping(document))?)
 }
 
-fn get_latest_account_version(account_name: &str) -> Result<String, io::Error> {
-    let dir = get_or_create_accounts_save_dir();
-    let account_folder = dir.join(account_name);
+#[test]
+fn test_schema_migrations() {
+    init_test_dirs();
+    let accounts = [
+        include_str!("fixtures/account_v0_baseline.yaml"),
+        include_str!("fixtures/account_v0_interest.yaml"),
+        include_str!("fixtures/account_v0_household.yaml"),
+        include_str!("fixtures/account_v1.yaml"),
+    ];
+    let parsed: Vec<Account> = accounts
+        .iter()
+        .map(|contents| from_document(ACCOUNTS, contents).unwrap())
+        .collect();
+    assert_eq!(parsed[0].cash_flows.len(), 2);
+    assert!(parsed[0].interest.is_none());
+    assert_eq!(parsed[1].interest.as_ref().unwrap().rate, 0.04);
+    assert_eq!(parsed[2].owner.as_deref(), Some("Alex"));
+    assert!(parsed[2].cash_flows[0].end_event.is_some());
+    assert_eq!(
+        parsed[3].id.to_string(),
+        "7c9e6679-7425-40de-944b-e07fc1f90ae7"
+    );
 
-    let mut versions = Vec::new();
+    // Every version is saved at the current one, and reads back the same
+    for account in &parsed {
+        let saved = to_document(ACCOUNTS, account).unwrap();
+        assert!(saved.starts_with("schema_version: 1\n"));
+        let reread: Account = from_document(ACCOUNTS, &saved).unwrap();
+        assert_eq!(
+            serde_yaml::to_value(&reread).unwrap(),
+            serde_yaml::to_value(account).unwrap()
+        );
+    }
 
-    if let Ok(entries) = fs::read_dir(&account_folder) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                if entry.file_type().unwrap().is_file() {
-                    if let Some(account_name) = entry.file_name().to_str() {
-                        if account_name.ends_with(".yaml") {
-                            versions.push(account_name.to_string());
-                        }
-                    }
-                }
-            }
+    // Upgrading fills in the fields that older versions left out
+    let mut baseline: Mapping = serde_yaml::from_str(accounts[0]).unwrap();
+    account_v1(&mut baseline);
+    assert!(baseline.contains_key("id") && baseline.contains_key("owner"));
+    assert_eq!(baseline["cash_flows"][1]["start_event"], Value::Null);
+
+    let scenario: Scenario =
+        from_document(SCENARIOS, include_str!("fixtures/scenario_v0.yaml")).unwrap();
+    assert_eq!(scenario.accounts[0].account.name, "Checking");
+    let portfolio: Portfolio =
+        from_document(PORTFOLIOS, include_str!("fixtures/portfolio_v0.yaml")).unwrap();
+    assert_eq!(portfolio.weights, vec![0.6, 0.4]);
+
+    // Saved items are upgraded when read back from storage
+    let name = "Test Schema Migrations";
+    storage()
+        .unwrap()
+        .write(
+            SCENARIOS,
+            name,
+            "20240101_000000",
+            include_str!("fixtures/scenario_v0.yaml"),
+        )
+        .unwrap();
+    assert_eq!(read_scenario(name).unwrap().num_samples, 1);
+    delete_scenario(name).unwrap();
+
+    let newer = "schema_version: 2\nname: Newer\n";
+    assert!(matches!(
+        from_document::<Account>(ACCOUNTS, newer),
+        Err(Error::Parse(_))
+    ));
+}
+
+fn write_versioned<T: Serialize>(collection: &str, name: &str, value: &T) -> Result<(), Error> {
+    // Saves within the same second still get their own, later version
+    let mut v = get_next_version();
+    if let Some(latest) = list_versions(collection, name)?.last() {
+        if v <= latest.version {
+            v = (latest.saved_at + chrono::Duration::seconds(1))
+                .format(VERSION_FORMAT)
+                .to_string();
         }
     }
 
-    versions.sort();
-    versions.last().cloned().ok_or(std::io::Error::new(
-        io::ErrorKind::NotFound,
-        "No versions found",
-    ))
+    storage()?.write(collection, name, &v, &to_document(collection, value)?)?;
+    Ok(())
+}
+
+fn list_versioned(collection: &str) -> Result<Vec<String>, Error> {
+    let mut names = storage()?.list(collection).unwrap_or_default();
+    names.sort();
+    Ok(names)
+}
+
+/// Every saved version of `name`, oldest first.
+fn list_versions(collection: &str, name: &str) -> Result<Vec<Version>, Error> {
+    let mut versions: Vec<Version> = storage()?
+        .versions(collection, name)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|version| {
+            let saved_at = chrono::NaiveDateTime::parse_from_str(&version, VERSION_FORMAT).ok()?;
+            Some(Version { version, saved_at })
+        })
+        .collect();
+
+    versions.sort_by(|a, b| a.version.cmp(&b.version));
+    Ok(versions)
+}
+
+fn get_latest_version(collection: &str, name: &str) -> Result<String, Error> {
+    list_versions(collection, name)?
+        .pop()
+        .map(|v| v.version)
+        .ok_or(Error::NotFound(format!("No saved versions of {}", name)))
+}
+
+fn read_versioned<T: DeserializeOwned>(collection: &str, name: &str) -> Result<T, Error> {
+    let latest_version = get_latest_version(collection, name)?;
+    read_version(collection, name, &latest_version)
+}
+
+fn read_version<T: DeserializeOwned>(
+    collection: &str,
+    name: &str,
+    version: &str,
+) -> Result<T, Error> {
+    let value_str = storage()?
+        .read(collection, name, version)
+        .map_err(|_| Error::NotFound(format!("Version {} of {} not found", version, name)))?;
+
+    from_document(collection, &value_str)
 }
 
-pub fn read_account(account_name: &str) -> Result<Account, serde_yaml::Error> {
-    let dir = get_or_create_accounts_save_dir();
-    let account_folder = dir.join(account_name);
+/// A deleted item, kept until it is restored or purged. `tombstone` identifies it, and ends with
+/// the time it was deleted so that items deleted under the same name don't overwrite each other.
+/// Older tombstones have no timestamp.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Deleted {
+    pub name: String,
+    pub tombstone: String,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+}
 
-    let latest_version = get_latest_account_version(account_name);
-    if latest_version.is_err() {
-        return Err(serde_yaml::Error::custom("No versions found"));
+fn delete_versioned(collection: &str, name: &str) -> Result<(), Error> {
+    let tombstones = storage()?.tombstones(collection)?;
+    let mut deleted_at =
+        chrono::NaiveDateTime::parse_from_str(&get_next_version(), VERSION_FORMAT).unwrap();
+    let mut tombstone = format!("{}_{}", name, deleted_at.format(VERSION_FORMAT));
+    while tombstones.contains(&tombstone) {
+        deleted_at += chrono::Duration::seconds(1);
+        tombstone = format!("{}_{}", name, deleted_at.format(VERSION_FORMAT));
     }
 
-    let account_path = account_folder.join(latest_version.unwrap());
+    storage()?.delete(collection, name, &tombstone)?;
+    Ok(())
+}
 
-    let account_str =
-        fs::read_to_string(account_path).expect("Could not read account file to string");
+fn list_deleted(collection: &str) -> Result<Vec<Deleted>, Error> {
+    let mut deleted: Vec<Deleted> = storage()?
+        .tombstones(collection)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tombstone| {
+            let timestamped = tombstone.rsplit_once('_').and_then(|(rest, time)| {
+                let (name, date) = rest.rsplit_once('_')?;
+                let deleted_at = chrono::NaiveDateTime::parse_from_str(
+                    &format!("{}_{}", date, time),
+                    VERSION_FORMAT,
+                )
+                .ok()?;
+                Some((name.to_string(), Some(deleted_at)))
+            });
+            let (name, deleted_at) = timestamped.unwrap_or((tombstone.clone(), None));
+            Deleted {
+                name,
+                tombstone,
+                deleted_at,
+            }
+        })
+        .collect();
 
-    serde_yaml::from_str(&account_str)
+    deleted.sort_by(|a, b| (&a.name, a.deleted_at).cmp(&(&b.name, b.deleted_at)));
+    Ok(deleted)
 }
 
-pub fn delete_account(account_name: &str) -> Result<(), std::io::Error> {
-    let dir = get_or_create_accounts_save_dir();
-    let account_folder = dir.join(account_name);
+fn find_deleted(collection: &str, tombstone: &str) -> Result<Deleted, Error> {
+    list_deleted(collection)?
+        .into_iter()
+        .find(|d| d.tombstone == tombstone)
+        .ok_or(Error::NotFound(format!("No deleted item {}", tombstone)))
+}
 
-    let deleted_folder = dir.join(".deleted").join(account_name);
-    std::fs::create_dir_all(&deleted_folder).expect("Could not create deleted folder");
-    // move the account files to the deleted folder
+/// The latest version of a deleted item.
+fn read_deleted<T: DeserializeOwned>(collection: &str, deleted: &Deleted) -> Result<T, Error> {
+    let mut versions =
+        storage()?.deleted_versions(collection, &deleted.tombstone, &deleted.name)?;
+    versions.sort();
+    let latest = versions.pop().ok_or(Error::NotFound(format!(
+        "No versions of deleted item {}",
+        deleted.tombstone
+    )))?;
+    let contents =
+        storage()?.read_deleted(collection, &deleted.tombstone, &deleted.name, &latest)?;
+    from_document(collection, &contents)
+}
 
-    for entry in fs::read_dir(&account_folder)?.into_iter() {
-        let entry = entry?;
-        let path = entry.path();
-        let file_name = path.file_name().unwrap().to_str().unwrap();
-        let deleted_path = deleted_folder.join(file_name);
-        fs::rename(path, deleted_path)?;
+/// The name to restore an item named `name` under: the name itself, or if it is `taken`,
+/// "<name> (restored)", "<name> (restored 2)" and so on.
+fn restored_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut restored = name.to_string();
+    let mut n = 1;
+    while taken(&restored) {
+        restored = match n {
+            1 => format!("{} (restored)", name),
+            _ => format!("{} (restored {})", name, n),
+        };
+        n += 1;
     }
+    restored
+}
 
-    // delete main account folder
-    fs::remove_dir_all(&account_folder)?;
+/// Permanently removes a deleted item.
+fn purge_versioned(collection: &str, tombstone: &str) -> Result<(), Error> {
+    find_deleted(collection, tombstone)?;
+    storage()?.purge(collection, tombstone)?;
+    Ok(())
+}
+
+// ACCOUNTS
 
+/// Removes the versions of `name` that `policy` doesn't keep, returning them.
+fn prune_versions(
+    collection: &str,
+    name: &str,
+    policy: &RetentionPolicy,
+) -> Result<Vec<Version>, Error> {
+    let pruned = policy.versions_to_prune(
+        &list_versions(collection, name)?,
+        chrono::Local::now().naive_local(),
+    );
+    for v in &pruned {
+        storage()?.remove(collection, name, &v.version)?;
+    }
+    Ok(pruned)
+}
+
+/// Saves a new version of the account, then prunes old versions under the retention policy
+/// in the app config. Fails if another account already has the same name.
+pub fn write_account_file(account: &Account) -> Result<(), Error> {
+    let fsn = account.fs_name();
+    with_account_ids(|ids| {
+        if ids.get(&account.name).is_some_and(|id| *id != fsn) {
+            return Err(Error::Validation(format!(
+                "Another account is already named {}",
+                account.name
+            )));
+        }
+        write_versioned(ACCOUNTS, &fsn, account)?;
+        ids.retain(|_, id| *id != fsn);
+        ids.insert(account.name.clone(), fsn.clone());
+        Ok(())
+    })??;
+
+    let policy = read_app_config().retention;
+    prune_versions(ACCOUNTS, &fsn, &policy)?;
     Ok(())
 }
+
+// The id each saved account name is saved under, so that looking an account up by name doesn't
+// read every account. Built on first use, and kept up to date as accounts are saved and deleted.
+static ACCOUNT_IDS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+fn with_account_ids<T>(f: impl FnOnce(&mut HashMap<String, String>) -> T) -> Result<T, Error> {
+    let mut index = ACCOUNT_IDS.lock().unwrap();
+    let ids = match index.take() {
+        Some(ids) => ids,
+        None => list_versioned(ACCOUNTS)?
+            .into_iter()
+            .filter_map(|id| {
+                let account = read_versioned::<Account>(ACCOUNTS, &id).ok()?;
+                Some((account.name, id))
+            })
+            .collect(),
+    };
+    Ok(f(index.insert(ids)))
+}
+
+// Rebuilds the index on next use, after accounts are moved or renamed in storage directly
+fn forget_account_ids() {
+    *ACCOUNT_IDS.lock().unwrap() = None;
+}
+
+// Accounts are saved under their id, so renaming one keeps its history, but can be looked up by
+// name or id, as names are unique. The id of the saved account named or identified by `account`.
+fn account_id(account: &str) -> Result<String, Error> {
+    if list_versioned(ACCOUNTS)?.iter().any(|id| id == account) {
+        return Ok(account.to_string());
+    }
+    with_account_ids(|ids| ids.get(account).cloned())?
+        .ok_or(Error::NotFound(format!("No saved account {}", account)))
+}
+
+/// Moves accounts saved under their name, from before accounts had ids, to their id, returning
+/// how many were moved.
+pub fn migrate_account_ids() -> Result<usize, Error> {
+    // Startup and tests can run this at once
+    static MIGRATING: Mutex<()> = Mutex::new(());
+    let _lock = MIGRATING.lock().unwrap();
+
+    let mut migrated = 0;
+    for name in list_versioned(ACCOUNTS)? {
+        if Uuid::parse_str(&name).is_ok() {
+            continue;
+        }
+        // Versions without an id get a new one each read, so every version gets the latest's
+        let Ok(latest) = read_versioned::<Account>(ACCOUNTS, &name) else {
+            continue;
+        };
+        let id = latest.fs_name();
+        for v in list_versions(ACCOUNTS, &name)? {
+            let Ok(mut account) = read_version::<Account>(ACCOUNTS, &name, &v.version) else {
+                continue;
+            };
+            account.id = latest.id;
+            storage()?.write(ACCOUNTS, &id, &v.version, &to_document(ACCOUNTS, &account)?)?;
+            storage()?.remove(ACCOUNTS, &name, &v.version)?;
+        }
+        migrated += 1;
+    }
+    forget_account_ids();
+    Ok(migrated)
+}
+
+#[test]
+fn test_migrate_account_ids() {
+    init_test_dirs();
+    let mut account = crate::sim::examples::simple_account::simple_account();
+    account.set_name("Test Legacy/Account".to_string());
+    // Saved by name, without an id
+    let legacy = to_yaml(&account)
+        .unwrap()
+        .replace(&format!("id: {}\n", account.id), "");
+    assert!(!legacy.contains("id:"));
+    for version in ["20240101_000000", "20240102_000000"] {
+        storage()
+            .unwrap()
+            .write(ACCOUNTS, &account.name, version, &legacy)
+            .unwrap();
+    }
+
+    assert!(migrate_account_ids().unwrap() >= 1);
+    assert!(!list_versioned(ACCOUNTS).unwrap().contains(&account.name));
+    let migrated = read_account(&account.name).unwrap();
+    let versions = list_account_versions(&account.name).unwrap();
+    assert_eq!(versions.len(), 2);
+    for v in versions {
+        let version = read_account_version(&migrated.fs_name(), &v.version).unwrap();
+        assert_eq!(version.id, migrated.id);
+    }
+
+    delete_account(&account.name).unwrap();
+}
+
+#[test]
+fn test_account_names_are_unique() {
+    init_test_dirs();
+    let mut first = crate::sim::examples::simple_account::simple_account();
+    first.set_name("Test Unique Name".to_string());
+    let mut second = crate::sim::examples::simple_account::simple_account();
+    second.set_name(first.name.clone());
+    assert_ne!(first.id, second.id);
+
+    write_account_file(&first).unwrap();
+    assert!(write_account_file(&second).is_err());
+    // Saving the same account again is fine
+    write_account_file(&first).unwrap();
+
+    // Renaming frees the old name for another account
+    first.set_name("Test Unique Name Renamed".to_string());
+    write_account_file(&first).unwrap();
+    write_account_file(&second).unwrap();
+    assert_eq!(read_account("Test Unique Name").unwrap().id, second.id);
+    assert_eq!(read_account(&first.name).unwrap().id, first.id);
+
+    delete_account(&first.name).unwrap();
+    delete_account(&second.name).unwrap();
+    assert!(read_account(&second.name).is_err());
+}
+
+pub fn list_accounts() -> Result<Vec<String>, Error> {
+    let mut names: Vec<String> = list_accounts_detail()?
+        .into_iter()
+        .map(|a| a.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+pub fn list_accounts_detail() -> Result<Vec<Account>, Error> {
+    Ok(list_versioned(ACCOUNTS)?
+        .iter()
+        .filter_map(|id| read_versioned(ACCOUNTS, id).ok())
+        .collect())
+}
+
+pub fn read_account(account_name: &str) -> Result<Account, Error> {
+    let id = account_id(account_name)?;
+    read_versioned(ACCOUNTS, &id)
+}
+
+pub fn delete_account(account_name: &str) -> Result<(), Error> {
+    let id = account_id(account_name)?;
+    delete_versioned(ACCOUNTS, &id)?;
+    with_account_ids(|ids| ids.retain(|_, i| *i != id))
+}
+
+/// Deleted accounts, under the name they had when they were deleted.
+pub fn list_deleted_accounts() -> Result<Vec<Deleted>, Error> {
+    let mut deleted: Vec<Deleted> = list_deleted(ACCOUNTS)?
+        .into_iter()
+        .map(|mut d| {
+            if let Ok(account) = read_deleted::<Account>(ACCOUNTS, &d) {
+                d.name = account.name;
+            }
+            d
+        })
+        .collect();
+    deleted.sort_by(|a, b| (&a.name, a.deleted_at).cmp(&(&b.name, b.deleted_at)));
+    Ok(deleted)
+}
+
+/// Restores a deleted account, returning its name. It keeps its id unless that has been saved
+/// again since, and if its name has been reused it is renamed in every version, as
+/// "<name> (restored)", "<name> (restored 2)" and so on.
+pub fn restore_deleted_account(tombstone: &str) -> Result<String, Error> {
+    let deleted = find_deleted(ACCOUNTS, tombstone)?;
+    let mut latest: Account = read_deleted(ACCOUNTS, &deleted)?;
+    if !storage()?.versions(ACCOUNTS, &latest.fs_name())?.is_empty() {
+        latest.id = Uuid::new_v4();
+    }
+    let names = list_accounts()?;
+    let name = restored_name(&latest.name, |n| names.iter().any(|taken| taken == n));
+
+    let id = latest.fs_name();
+    storage()?.restore(ACCOUNTS, tombstone, &deleted.name, &id)?;
+    for v in list_versions(ACCOUNTS, &id)? {
+        let mut account: Account = read_version(ACCOUNTS, &id, &v.version)?;
+        if account.id != latest.id || name != latest.name {
+            account.id = latest.id;
+            if name != latest.name {
+                account.set_name(name.clone());
+            }
+            storage()?.write(ACCOUNTS, &id, &v.version, &to_document(ACCOUNTS, &account)?)?;
+        }
+    }
+    forget_account_ids();
+    Ok(name)
+}
+
+pub fn purge_deleted_account(tombstone: &str) -> Result<(), Error> {
+    purge_versioned(ACCOUNTS, tombstone)
+}
+
+#[test]
+fn test_deleted_accounts() {
+    init_test_dirs();
+    let mut account = crate::sim::examples::simple_account::simple_account();
+    account.set_name("Test Deleted Accounts".to_string());
+    let name = account.name.clone();
+    let tombstones = || -> Vec<Deleted> {
+        list_deleted_accounts()
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.name == name)
+            .collect()
+    };
+    // Deleting the same account twice keeps both
+    write_account_file(&account).unwrap();
+    delete_account(&name).unwrap();
+    write_account_file(&account).unwrap();
+    delete_account(&name).unwrap();
+    let deleted = tombstones();
+    assert_eq!(deleted.len(), 2);
+    assert!(deleted.iter().all(|d| d.deleted_at.is_some()));
+    assert!(read_account(&name).is_err());
+
+    restore_deleted_account(&deleted[0].tombstone).unwrap();
+    assert_eq!(read_account(&name).unwrap(), account);
+
+    // The name and id are taken now, so the second gets new ones
+    let restored = restore_deleted_account(&deleted[1].tombstone).unwrap();
+    assert_eq!(restored, format!("{} (restored)", name));
+    let restored_account = read_account(&restored).unwrap();
+    assert_eq!(restored_account.name, restored);
+    assert_ne!(restored_account.id, account.id);
+    assert!(tombstones().is_empty());
+
+    delete_account(&name).unwrap();
+    purge_deleted_account(&tombstones()[0].tombstone).unwrap();
+    assert!(tombstones().is_empty());
+    assert!(purge_deleted_account("Not A Real Tombstone 1234").is_err());
+    delete_account(&restored).unwrap();
+}
+
+/// Every saved version of an account, oldest first, or none if there is no such account.
+pub fn list_account_versions(account_name: &str) -> Result<Vec<Version>, Error> {
+    match account_id(account_name) {
+        Ok(id) => list_versions(ACCOUNTS, &id),
+        Err(Error::NotFound(_)) => Ok(vec![]),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn read_account_version(account_name: &str, version: &str) -> Result<Account, Error> {
+    let id = account_id(account_name)?;
+    read_version(ACCOUNTS, &id, version)
+}
+
+/// The versions of an account that the retention policy in the app config would prune now.
+pub fn list_account_versions_to_prune(account_name: &str) -> Result<Vec<Version>, Error> {
+    Ok(read_app_config().retention.versions_to_prune(
+        &list_account_versions(account_name)?,
+        chrono::Local::now().naive_local(),
+    ))
+}
+
+/// The changes to an account from version `from` to version `to`.
+pub fn diff_account_versions(
+    account_name: &str,
+    from: &str,
+    to: &str,
+) -> Result<AccountDiff, Error> {
+    let from = read_account_version(account_name, from)?;
+    let to = read_account_version(account_name, to)?;
+    Ok(AccountDiff::new(&from, &to))
+}
+
+/// Saves an old version of an account again as its newest version, keeping the versions since.
+pub fn restore_account_version(account_name: &str, version: &str) -> Result<Account, Error> {
+    let account = read_account_version(account_name, version)?;
+    write_account_file(&account)?;
+    Ok(account)
+}
+
+#[test]
+fn test_account_versions() {
+    init_test_dirs();
+    let mut account = crate::sim::examples::simple_account::simple_account();
+    account.set_name("Test Account Versions".to_string());
+    let name = account.name.clone();
+    write_account_file(&account).unwrap();
+    account.balance = 20_000.0;
+    account.cash_flows.pop();
+    write_account_file(&account).unwrap();
+
+    let versions = list_account_versions(&name).unwrap();
+    assert_eq!(versions.len(), 2);
+    assert!(versions[0].saved_at < versions[1].saved_at);
+
+    let (first, second) = (&versions[0].version, &versions[1].version);
+    assert_eq!(
+        read_account_version(&name, first).unwrap().balance,
+        10_000.0
+    );
+    let diff = diff_account_versions(&name, first, second).unwrap();
+    assert_eq!(diff.fields, vec!["balance".to_string()]);
+    assert_eq!(diff.cash_flows.len(), 1);
+
+    restore_account_version(&name, first).unwrap();
+    assert_eq!(list_account_versions(&name).unwrap().len(), 3);
+    assert_eq!(read_account(&name).unwrap().balance, 10_000.0);
+    assert!(read_account_version(&name, "20000101_000000").is_err());
+
+    delete_account(&name).unwrap();
+}
+
+// SCENARIOS
+
+/// Saves a new version of the scenario named `name`, with its accounts, portfolios, transfers
+/// and dates, so that it can be rerun exactly as it was built.
+pub fn write_scenario_file(name: &str, scenario: &Scenario) -> Result<(), Error> {
+    write_versioned(SCENARIOS, name, scenario)
+}
+
+pub fn list_scenarios() -> Result<Vec<String>, Error> {
+    list_versioned(SCENARIOS)
+}
+
+pub fn read_scenario(name: &str) -> Result<Scenario, Error> {
+    read_versioned(SCENARIOS, name)
+}
+
+pub fn delete_scenario(name: &str) -> Result<(), Error> {
+    delete_versioned(SCENARIOS, name)
+}
+
+#[test]
+fn test_scenario_store() {
+    init_test_dirs();
+    let name = "Test Scenario Store";
+    let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+    scenario.num_samples = 7;
+    write_scenario_file(name, &scenario).unwrap();
+    assert!(list_scenarios().unwrap().contains(&name.to_string()));
+
+    let saved = read_scenario(name).unwrap();
+    assert_eq!(saved.num_samples, 7);
+    assert_eq!(saved.accounts.len(), scenario.accounts.len());
+    assert_eq!(saved.transfers.len(), scenario.transfers.len());
+    assert_eq!(saved.start_date, scenario.start_date);
+
+    delete_scenario(name).unwrap();
+    assert!(!list_scenarios().unwrap().contains(&name.to_string()));
+    assert!(read_scenario(name).is_err());
+}
+
+// PORTFOLIOS
+
+/// Saves a new version of the portfolio named `name`, if its weights are valid.
+pub fn write_portfolio_file(name: &str, portfolio: &Portfolio) -> Result<(), Error> {
+    check(portfolio.validate())?;
+    write_versioned(PORTFOLIOS, name, portfolio)
+}
+
+pub fn list_portfolios() -> Result<Vec<String>, Error> {
+    list_versioned(PORTFOLIOS)
+}
+
+pub fn list_portfolios_detail() -> Result<HashMap<String, Portfolio>, Error> {
+    let mut portfolio_details = HashMap::new();
+    for name in list_portfolios()? {
+        if let Ok(portfolio) = read_portfolio(&name) {
+            portfolio_details.insert(name, portfolio);
+        }
+    }
+    Ok(portfolio_details)
+}
+
+pub fn read_portfolio(name: &str) -> Result<Portfolio, Error> {
+    let portfolio: Portfolio = read_versioned(PORTFOLIOS, name)?;
+    check(portfolio.validate())?;
+    Ok(portfolio)
+}
+
+pub fn delete_portfolio(name: &str) -> Result<(), Error> {
+    delete_versioned(PORTFOLIOS, name)
+}
+
+#[test]
+fn test_portfolio_store() {
+    init_test_dirs();
+    let name = "Test Portfolio Store";
+    let portfolio = Portfolio::default().unwrap();
+    write_portfolio_file(name, &portfolio).unwrap();
+    assert!(list_portfolios().unwrap().contains(&name.to_string()));
+    assert_eq!(list_portfolios_detail().unwrap()[name].weights, vec![1.0]);
+
+    let mut invalid = portfolio.clone();
+    invalid.weights = vec![0.5];
+    assert!(write_portfolio_file(name, &invalid).is_err());
+    assert_eq!(read_portfolio(name).unwrap().weights, vec![1.0]);
+
+    delete_portfolio(name).unwrap();
+    assert!(read_portfolio(name).is_err());
+}
+
+/// Moves portfolios saved as YAML files in the save dir itself, from before the portfolio store,
+/// into it, returning how many were moved. Like the old portfolio list, this picks up files
+/// with `portfolio` in their name, and each is named after its file. Files that can't be read
+/// as a valid portfolio, or whose name is already taken, are left where they are.
+pub fn migrate_legacy_portfolios() -> Result<usize, Error> {
+    migrate_legacy_portfolios_from(&save_dir()?)
+}
+
+fn migrate_legacy_portfolios_from(dir: &Path) -> Result<usize, Error> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let portfolios = list_portfolios()?;
+    let mut migrated = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .filter(|f| f.contains("portfolio"))
+            .and_then(|f| f.strip_suffix(".yaml"))
+        else {
+            continue;
+        };
+        if !path.is_file() || portfolios.iter().any(|p| p == name) {
+            continue;
+        }
+        let Ok(portfolio) = serde_yaml::from_str::<Portfolio>(&fs::read_to_string(&path)?) else {
+            continue;
+        };
+        if write_portfolio_file(name, &portfolio).is_ok() {
+            fs::remove_file(&path)?;
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+#[test]
+fn test_migrate_legacy_portfolios() {
+    init_test_dirs();
+    let dir = std::env::temp_dir().join(format!("tortoise-portfolios-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let portfolio = Portfolio::default().unwrap();
+    let legacy = dir.join("test_legacy_portfolio.yaml");
+    fs::write(&legacy, to_yaml(&portfolio).unwrap()).unwrap();
+    let unreadable = dir.join("broken_portfolio.yaml");
+    fs::write(&unreadable, "weights: [").unwrap();
+    fs::write(dir.join("notes.yaml"), "a: 1").unwrap();
+
+    assert_eq!(migrate_legacy_portfolios_from(&dir).unwrap(), 1);
+    assert!(!legacy.exists());
+    assert!(unreadable.exists());
+    let moved = read_portfolio("test_legacy_portfolio").unwrap();
+    assert_eq!(moved.weights, portfolio.weights);
+    assert_eq!(migrate_legacy_portfolios_from(&dir).unwrap(), 0);
+
+    delete_portfolio("test_legacy_portfolio").unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
+
+// RETURNS
+
+// Historical returns datasets are CSV files in the `returns` folder, named after the dataset
+
+pub fn list_returns_datasets() -> Result<Vec<String>, Error> {
+    let mut names: Vec<String> = storage()?
+        .list_files(RETURNS)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|f| f.strip_suffix(".csv"))
+        .map(|f| f.to_string())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+pub fn read_returns_dataset(name: &str) -> Result<ReturnsDataset, Error> {
+    let csv = storage()?
+        .read_file(RETURNS, &format!("{}.csv", name))
+        .map_err(|_| Error::NotFound(format!("Unknown returns dataset: {}", name)))?;
+    ReturnsDataset::from_csv(name, &csv)
+}
+
+#[test]
+fn test_read_returns_dataset() {
+    init_test_dirs();
+    storage()
+        .unwrap()
+        .write_file(
+            RETURNS,
+            "Test Returns.csv",
+            "month,Stocks\n2008-01,-0.06\n2008-02,-0.03\n",
+        )
+        .unwrap();
+
+    assert!(list_returns_datasets()
+        .unwrap()
+        .contains(&"Test Returns".to_string()));
+    let dataset = read_returns_dataset("Test Returns").unwrap();
+    assert_eq!(dataset.returns.len(), 2);
+    assert!(read_returns_dataset("Not A Real Dataset 1234").is_err());
+}
@@ -4,21 +4,40 @@
 use tauri::menu::{Menu, PredefinedMenuItem, Submenu};
 
 pub mod api;
+pub mod config;
+pub mod error;
+pub mod import;
 pub mod io;
 pub mod sim;
+pub mod storage;
 
+// A task that fails is reported and skipped, so that the app still starts and each command can
+// report the problem when it is used.
 fn startup_tasks() {
-    let example_account = sim::examples::simple_account::simple_account();
+    if let Err(e) = io::migrate_account_ids() {
+        eprintln!("Could not migrate accounts: {}", e);
+    }
+    if let Err(e) = io::migrate_legacy_portfolios() {
+        eprintln!("Could not migrate portfolios: {}", e);
+    }
 
-    if !io::list_accounts().contains(&"Example".to_string()) {
-        io::write_account_file(&example_account);
+    let example_account = sim::examples::simple_account::simple_account();
+    let saved = io::list_accounts().and_then(|accounts| {
+        match accounts.contains(&example_account.name) {
+            true => Ok(()),
+            false => io::write_account_file(&example_account),
+        }
+    });
+    if let Err(e) = saved {
+        eprintln!("Could not save the example account: {}", e);
     }
 }
 
 #[test]
 fn test_startup_tasks() {
+    io::init_test_dirs();
     startup_tasks();
-    let accounts = io::list_accounts();
+    let accounts = io::list_accounts().unwrap();
     assert!(accounts.contains(&"Example".to_string()));
 }
 
@@ -60,13 +79,50 @@ pub fn run() {
             api::sim::list_available_accounts,
             api::sim::list_available_accounts_detail,
             api::sim::list_available_portfolios,
+            api::sim::list_available_portfolios_detail,
+            api::sim::get_portfolio_config,
+            api::sim::save_portfolio_config,
+            api::sim::delete_portfolio,
             api::sim::get_cash_flows_from_config,
             api::sim::get_account_config,
             api::sim::save_account_config,
+            api::sim::validate_account,
+            api::sim::validate_portfolio,
+            api::sim::validate_scenario,
             api::sim::delete_account,
+            api::sim::list_deleted_accounts,
+            api::sim::restore_deleted_account,
+            api::sim::purge_deleted_account,
+            api::sim::list_account_versions,
+            api::sim::get_account_version,
+            api::sim::list_account_versions_to_prune,
+            api::sim::get_app_config,
+            api::sim::save_app_config,
+            api::sim::get_data_dir,
+            api::sim::migrate_data_dir,
+            api::sim::diff_account_versions,
+            api::sim::restore_account_version,
+            api::sim::compare_debt_strategies,
+            api::sim::solve_scenario,
+            api::sim::analyze_sensitivity,
+            api::sim::compare_scenarios,
+            api::sim::list_returns_datasets,
+            api::sim::run_historical_replay,
+            api::sim::run_rolling_replay,
+            api::sim::list_saved_scenarios,
+            api::sim::get_saved_scenario,
+            api::sim::save_scenario,
+            api::sim::delete_saved_scenario,
+            api::sim::run_saved_scenario,
 
             api::import::import_account,
             api::import::get_file_path,
+            api::import::read_csv_headers,
+            api::import::import_csv_transactions,
+            api::import::detect_recurring_flows,
+            api::import::import_ofx_statements,
+            api::import::account_from_ofx_statement,
+            api::import::portfolio_from_ofx_statement,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,5 +1,50 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+// Release builds on Windows use the GUI subsystem, which starts without a console, so
+// subcommands and errors print to the console of the shell the app was started from.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails harmlessly when the app wasn't started from a shell
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 {
+        attach_console();
+    }
+    let (options, args) = match tortoise::api::cli::global_options(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = tortoise::io::init_data_dir(options.data_dir, options.portable) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    let storage = tortoise::io::storage_from_config(&tortoise::io::read_app_config())
+        .map_err(|e| e.to_string())
+        .and_then(tortoise::io::init_storage);
+    if let Err(e) = storage {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(code) = tortoise::api::cli::run(&args) {
+        std::process::exit(code);
+    }
+
     tortoise::run();
 }
@@ -1,8 +1,12 @@
+use super::household::LifeEvent;
+use super::interest::InterestRate;
+use super::validation::{nested, Problem};
 use chrono::{Datelike, NaiveDate};
 use ndarray::Array1;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use uuid::Uuid;
 
 #[allow(unused_imports)]
 use std::hash::{Hash, Hasher};
@@ -133,6 +137,10 @@ pub struct CashFlow {
     pub end_date: Option<chrono::NaiveDate>,
     pub tax_rate: f64,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub start_event: Option<LifeEvent>,
+    #[serde(default)]
+    pub end_event: Option<LifeEvent>,
 }
 
 impl CashFlow {
@@ -154,6 +162,8 @@ impl CashFlow {
             end_date,
             tax_rate: tax_rate.unwrap_or(0.0),
             tags,
+            start_event: None,
+            end_event: None,
         }
     }
 
@@ -161,6 +171,34 @@ impl CashFlow {
         self.name = Some(name);
     }
 
+    /// Every problem that would stop the flow from being simulated.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        if !self.amount.is_finite() {
+            problems.push(Problem::new("amount", "is not a number"));
+        }
+        if !(0.0..=1.0).contains(&self.tax_rate) {
+            problems.push(Problem::new("tax_rate", "must be between 0 and 1"));
+        }
+        // Tax payments are named after the flow
+        if self.tax_rate != 0.0 && self.name.is_none() {
+            problems.push(Problem::new("name", "is needed for a taxed cash flow"));
+        }
+        let dated = matches!(self.frequency, Frequency::Once | Frequency::Annually);
+        if dated && self.start_date.is_none() && self.start_event.is_none() {
+            problems.push(Problem::new(
+                "start_date",
+                format!("is needed for a {:?} cash flow", self.frequency),
+            ));
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if end < start {
+                problems.push(Problem::new("end_date", "is before the start date"));
+            }
+        }
+        problems
+    }
+
     pub fn payments(
         &self,
         start_date: chrono::NaiveDate,
@@ -204,14 +242,23 @@ impl CashFlow {
     }
 }
 
+/// An account, identified by `id` so that it can be renamed without losing its saved history.
+/// Accounts saved before ids were added get a new one when read.
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, TS)]
 #[ts(export, export_to = "../src/rustTypes/")]
 pub struct Account {
+    #[serde(default = "Uuid::new_v4")]
+    #[ts(type = "string")]
+    pub id: Uuid,
     pub name: String,
     pub balance: f64,
     pub cash_flows: Vec<CashFlow>,
     pub start_date: chrono::NaiveDate,
     pub end_date: chrono::NaiveDate,
+    #[serde(default)]
+    pub interest: Option<InterestRate>,
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 impl Account {
@@ -223,16 +270,20 @@ impl Account {
         end_date: chrono::NaiveDate,
     ) -> Account {
         Account {
+            id: Uuid::new_v4(),
             name,
             balance,
             cash_flows,
             start_date,
             end_date,
+            interest: None,
+            owner: None,
         }
     }
 
+    /// The key the account is saved under.
     pub fn fs_name(&self) -> String {
-        self.name.clone()
+        self.id.to_string()
     }
 
     pub fn set_name(&mut self, name: String) {
@@ -241,18 +292,46 @@ impl Account {
 
     pub fn default() -> Account {
         Account {
+            id: Uuid::new_v4(),
             name: "Account".to_string(),
             balance: 0.0,
             cash_flows: vec![],
             start_date: chrono::NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
             end_date: chrono::NaiveDate::from_ymd_opt(2999, 12, 31).unwrap(),
+            interest: None,
+            owner: None,
         }
     }
 
+    pub fn set_interest(&mut self, interest: Option<InterestRate>) {
+        self.interest = interest;
+    }
+
     pub fn add_cash_flow(&mut self, cash_flow: CashFlow) {
         self.cash_flows.push(cash_flow);
     }
 
+    /// Every problem with the account and its cash flows that would stop it from being simulated.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        if self.name.trim().is_empty() {
+            problems.push(Problem::new("name", "is empty"));
+        }
+        if !self.balance.is_finite() {
+            problems.push(Problem::new("balance", "is not a number"));
+        }
+        if self.end_date < self.start_date {
+            problems.push(Problem::new("end_date", "is before the start date"));
+        }
+        if let Some(interest) = &self.interest {
+            problems.extend(nested("interest", interest.validate()));
+        }
+        for (i, cash_flow) in self.cash_flows.iter().enumerate() {
+            problems.extend(nested(&format!("cash_flows[{}]", i), cash_flow.validate()));
+        }
+        problems
+    }
+
     pub fn payments(
         &mut self,
         start_date: chrono::NaiveDate,
@@ -297,7 +376,7 @@ impl Account {
 
 impl std::cmp::PartialEq for Account {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
+        self.id == other.id
     }
 }
 
@@ -305,13 +384,13 @@ impl std::cmp::Eq for Account {}
 
 impl std::hash::Hash for Account {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
+        self.id.hash(state);
     }
 }
 
 #[test]
 fn test_account_hash() {
-    // test that the account hash is based on the name
+    // test that the account hash is based on the id, not the name
     let account1 = Account::new(
         "Test Account".to_string(),
         0.0,
@@ -329,7 +408,7 @@ fn test_account_hash() {
     );
 
     let account2 = Account::new(
-        "Test Account 2".to_string(),
+        "Test Account".to_string(),
         0.0,
         vec![CashFlow::new(
             Some("Test Cash Flow".to_string()),
@@ -353,6 +432,59 @@ fn test_account_hash() {
     let hash2 = hasher.finish();
 
     assert_ne!(hash1, hash2);
+    assert_ne!(account1, account2);
+
+    let mut renamed = account1.clone();
+    renamed.set_name("Renamed Account".to_string());
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    renamed.hash(&mut hasher);
+    assert_eq!(hasher.finish(), hash1);
+    assert_eq!(renamed, account1);
+    assert_eq!(renamed.fs_name(), account1.id.to_string());
+}
+
+#[test]
+fn test_validate_account() {
+    let mut account = super::examples::simple_account::simple_account();
+    assert!(account.validate().is_empty());
+
+    account.name = " ".to_string();
+    account.balance = f64::NAN;
+    use super::interest::{Compounding, DayCount};
+    let mut interest = InterestRate::new(f64::NAN, Compounding::Monthly, DayCount::Act365);
+    interest.overdraft_rate = Some(-0.2);
+    account.set_interest(Some(interest));
+    let mut annual = CashFlow::new(
+        None,
+        100.0,
+        Some(Frequency::Annually),
+        None,
+        None,
+        Some(0.2),
+        None,
+    );
+    account.add_cash_flow(annual.clone());
+    let paths: Vec<String> = account.validate().into_iter().map(|p| p.path).collect();
+    let i = account.cash_flows.len() - 1;
+    assert_eq!(
+        paths,
+        vec![
+            "name".to_string(),
+            "balance".to_string(),
+            "interest.rate".to_string(),
+            "interest.overdraft_rate".to_string(),
+            format!("cash_flows[{}].name", i),
+            format!("cash_flows[{}].start_date", i),
+        ]
+    );
+
+    // A life event can stand in for the start date
+    annual.start_event = Some(LifeEvent::new(
+        None,
+        super::household::LifeEventKind::Retirement,
+    ));
+    annual.set_name("Bonus".to_string());
+    assert!(annual.validate().is_empty());
 }
 
 pub fn get_account_balance_at(
@@ -0,0 +1,439 @@
+use super::cash::{Account, CashFlow};
+use super::solver::DEFAULT_SEED;
+use super::{goal, simulate, BalancePercentiles, Scenario, SimulationResult};
+use crate::error::Error;
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use ts_rs::TS;
+
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A cash flow that differs between two scenarios. `fields` lists what changed.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct CashFlowDiff {
+    pub name: String,
+    pub change: Change,
+    pub fields: Vec<String>,
+}
+
+/// An account that differs between two scenarios, with the settings and cash flows that changed.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct AccountDiff {
+    pub account: String,
+    pub change: Change,
+    pub fields: Vec<String>,
+    pub cash_flows: Vec<CashFlowDiff>,
+}
+
+impl AccountDiff {
+    /// The settings and cash flows of `other` that differ from `baseline`, such as two saved
+    /// versions of the same account.
+    pub fn new(baseline: &Account, other: &Account) -> AccountDiff {
+        AccountDiff {
+            account: baseline.name.clone(),
+            change: Change::Changed,
+            fields: changed_fields(&without_cash_flows(baseline), &without_cash_flows(other)),
+            cash_flows: diff_cash_flows(&baseline.cash_flows, &other.cash_flows),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.cash_flows.is_empty()
+    }
+}
+
+/// The differences in the inputs of a scenario from the baseline. `fields` lists the scenario
+/// settings that changed, such as `loans` or `end_date`.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct ScenarioDiff {
+    pub fields: Vec<String>,
+    pub accounts: Vec<AccountDiff>,
+}
+
+impl ScenarioDiff {
+    pub fn new(baseline: &Scenario, other: &Scenario) -> ScenarioDiff {
+        let mut accounts = vec![];
+        let account =
+            |s: &Scenario, name: &str| s.accounts.iter().find(|a| a.account.name == name).cloned();
+        for a in &baseline.accounts {
+            let name = &a.account.name;
+            match account(other, name) {
+                None => accounts.push(AccountDiff {
+                    account: name.clone(),
+                    change: Change::Removed,
+                    fields: vec![],
+                    cash_flows: vec![],
+                }),
+                Some(b) => {
+                    // The portfolio is compared along with the account's own settings
+                    let mut diff = AccountDiff::new(&a.account, &b.account);
+                    if to_value(&a.portfolio) != to_value(&b.portfolio) {
+                        diff.fields.push("portfolio".to_string());
+                    }
+                    if !diff.is_empty() {
+                        accounts.push(diff);
+                    }
+                }
+            }
+        }
+        for b in &other.accounts {
+            if account(baseline, &b.account.name).is_none() {
+                accounts.push(AccountDiff {
+                    account: b.account.name.clone(),
+                    change: Change::Added,
+                    fields: vec![],
+                    cash_flows: vec![],
+                });
+            }
+        }
+
+        let fields = changed_fields(&without_accounts(baseline), &without_accounts(other))
+            .into_iter()
+            .filter(|f| f != "seed")
+            .collect();
+        ScenarioDiff { fields, accounts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.accounts.is_empty()
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+// Ids aren't compared either, since accounts saved before they had ids get a new one per read
+fn without_cash_flows(account: &Account) -> Account {
+    let mut account = account.clone();
+    account.id = uuid::Uuid::nil();
+    account.cash_flows = vec![];
+    account
+}
+
+fn without_accounts(scenario: &Scenario) -> Scenario {
+    let mut scenario = scenario.clone();
+    scenario.accounts = vec![];
+    scenario
+}
+
+// The names of the top-level fields whose serialized values differ
+fn changed_fields<T: Serialize>(a: &T, b: &T) -> Vec<String> {
+    match (to_value(a), to_value(b)) {
+        (Value::Object(a), Value::Object(b)) => a
+            .iter()
+            .filter(|(k, v)| b.get(*k) != Some(v))
+            .map(|(k, _)| k.clone())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// Cash flows are matched by name, falling back to their position for unnamed flows
+fn diff_cash_flows(baseline: &[CashFlow], other: &[CashFlow]) -> Vec<CashFlowDiff> {
+    let key = |i: usize, cf: &CashFlow| cf.name.clone().unwrap_or(format!("Cash Flow {}", i + 1));
+    let index = |flows: &[CashFlow]| -> BTreeMap<String, CashFlow> {
+        flows
+            .iter()
+            .enumerate()
+            .map(|(i, cf)| (key(i, cf), cf.clone()))
+            .collect()
+    };
+    let (a, b) = (index(baseline), index(other));
+
+    let mut diffs = vec![];
+    for (name, cf) in &a {
+        match b.get(name) {
+            None => diffs.push(CashFlowDiff {
+                name: name.clone(),
+                change: Change::Removed,
+                fields: vec![],
+            }),
+            Some(other) => {
+                let fields = changed_fields(cf, other);
+                if !fields.is_empty() {
+                    diffs.push(CashFlowDiff {
+                        name: name.clone(),
+                        change: Change::Changed,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    for name in b.keys().filter(|name| !a.contains_key(*name)) {
+        diffs.push(CashFlowDiff {
+            name: name.clone(),
+            change: Change::Added,
+            fields: vec![],
+        });
+    }
+    diffs
+}
+
+/// The difference of a scenario's mean and percentile balances from the baseline on `date`, for
+/// each reporting date the two scenarios share.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct BalanceDelta {
+    pub date: NaiveDate,
+    pub mean: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+/// Balance deltas of an account present in both the baseline and the compared scenario.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct AccountDelta {
+    pub account: String,
+    pub deltas: Vec<BalanceDelta>,
+}
+
+/// Mean net worth, the sum of every account and loan balance, of the baseline and the compared
+/// scenario on `date`.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct NetWorthDelta {
+    pub date: NaiveDate,
+    pub baseline: f64,
+    pub balance: f64,
+    pub delta: f64,
+}
+
+/// How the scenario at position `scenario` differs from the baseline, in its inputs and its
+/// results. Deltas cover the dates both scenarios simulate.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct ScenarioDelta {
+    pub scenario: usize,
+    pub diff: ScenarioDiff,
+    pub net_worth: Vec<NetWorthDelta>,
+    pub accounts: Vec<AccountDelta>,
+}
+
+/// `ScenarioComparison` holds the results of several scenarios run with the same `seed`, and
+/// each scenario's differences from the first.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct ScenarioComparison {
+    #[ts(type = "number")]
+    pub seed: u64,
+    pub results: Vec<HashMap<String, SimulationResult>>,
+    pub deltas: Vec<ScenarioDelta>,
+}
+
+fn net_worth(results: &HashMap<String, SimulationResult>) -> BTreeMap<NaiveDate, f64> {
+    let mut totals = BTreeMap::new();
+    for r in results.values() {
+        for b in &r.balances {
+            *totals.entry(b.date).or_insert(0.0) += b.balance;
+        }
+    }
+    totals
+}
+
+fn account_delta(
+    account: &str,
+    baseline: &SimulationResult,
+    other: &SimulationResult,
+) -> AccountDelta {
+    let mean = |r: &SimulationResult| -> HashMap<NaiveDate, f64> {
+        r.balances.iter().map(|b| (b.date, b.balance)).collect()
+    };
+    let (base_mean, other_mean) = (mean(baseline), mean(other));
+    let base_percentiles: HashMap<NaiveDate, &BalancePercentiles> =
+        baseline.percentiles.iter().map(|p| (p.date, p)).collect();
+    let deltas = other
+        .percentiles
+        .iter()
+        .filter_map(|p| {
+            let base = base_percentiles.get(&p.date)?;
+            Some(BalanceDelta {
+                date: p.date,
+                mean: other_mean[&p.date] - base_mean[&p.date],
+                p10: p.p10 - base.p10,
+                p50: p.p50 - base.p50,
+                p90: p.p90 - base.p90,
+            })
+        })
+        .collect();
+    AccountDelta {
+        account: account.to_string(),
+        deltas,
+    }
+}
+
+/// Runs every scenario with the same seed, so that differences in results come from the inputs,
+/// and compares each one after the first to the first.
+///
+/// The seed is `seed` if given, then the baseline scenario's own seed, then a fixed default.
+pub fn compare(scenarios: &[Scenario], seed: Option<u64>) -> Result<ScenarioComparison, Error> {
+    let baseline = scenarios.first().ok_or(Error::Validation(
+        "At least one scenario is needed".to_string(),
+    ))?;
+    let seed = seed.or(baseline.seed).unwrap_or(DEFAULT_SEED);
+
+    let results = scenarios
+        .iter()
+        .map(|s| {
+            let mut s = s.clone();
+            s.seed = Some(seed);
+            let mut results = simulate(s.clone())?;
+            goal::size_contributions(&s, &mut results)?;
+            Ok(results)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let base_net_worth = net_worth(&results[0]);
+    let mut deltas = vec![];
+    for (i, (scenario, r)) in scenarios.iter().zip(&results).enumerate().skip(1) {
+        let net_worth = net_worth(r)
+            .into_iter()
+            .filter_map(|(date, balance)| {
+                let baseline = *base_net_worth.get(&date)?;
+                Some(NetWorthDelta {
+                    date,
+                    baseline,
+                    balance,
+                    delta: balance - baseline,
+                })
+            })
+            .collect();
+
+        let mut names: Vec<&String> = r.keys().filter(|k| results[0].contains_key(*k)).collect();
+        names.sort();
+        let accounts = names
+            .into_iter()
+            .map(|name| account_delta(name, &results[0][name], &r[name]))
+            .collect();
+
+        deltas.push(ScenarioDelta {
+            scenario: i,
+            diff: ScenarioDiff::new(baseline, scenario),
+            net_worth,
+            accounts,
+        });
+    }
+
+    Ok(ScenarioComparison {
+        seed,
+        results,
+        deltas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::examples::mortgage_scenario::mortgage_scenario;
+    use crate::sim::examples::simple_account::simple_account;
+    use crate::sim::portfolio::Portfolio;
+    use crate::sim::InvestedAccount;
+
+    fn renting() -> Scenario {
+        let mut scenario = mortgage_scenario();
+        scenario.loans = vec![];
+        let rent = CashFlow::new(
+            Some("Rent".to_string()),
+            -36_000.0,
+            Some(crate::sim::cash::Frequency::MonthStart),
+            None,
+            None,
+            None,
+            Some(vec!["Expenses".to_string()]),
+        );
+        scenario.accounts[0].account.add_cash_flow(rent);
+        scenario
+    }
+
+    #[test]
+    fn test_scenario_diff() {
+        let renting = renting();
+        let mut buying = mortgage_scenario();
+        buying.accounts[0].account.cash_flows[0].amount *= 1.1;
+        buying.accounts.push(InvestedAccount {
+            account: simple_account(),
+            portfolio: Portfolio::default(),
+        });
+
+        let diff = ScenarioDiff::new(&renting, &buying);
+        assert_eq!(diff.fields, vec!["loans".to_string()]);
+        assert_eq!(diff.accounts.len(), 2);
+
+        let checking = &diff.accounts[0];
+        assert_eq!(checking.change, Change::Changed);
+        assert!(checking.fields.is_empty());
+        assert_eq!(
+            checking.cash_flows,
+            vec![
+                CashFlowDiff {
+                    name: "Income".to_string(),
+                    change: Change::Changed,
+                    fields: vec!["amount".to_string()],
+                },
+                CashFlowDiff {
+                    name: "Rent".to_string(),
+                    change: Change::Removed,
+                    fields: vec![],
+                },
+            ]
+        );
+        assert_eq!(diff.accounts[1].account, "Example");
+        assert_eq!(diff.accounts[1].change, Change::Added);
+
+        assert!(ScenarioDiff::new(&renting, &renting).is_empty());
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut renting = renting();
+        renting.num_samples = 5;
+        renting.accounts[0].portfolio = Portfolio::default();
+        let mut buying = mortgage_scenario();
+        buying.num_samples = 5;
+        buying.accounts[0].portfolio = Portfolio::default();
+
+        let c = compare(&[renting.clone(), buying], None).unwrap();
+        assert_eq!(c.results.len(), 2);
+        assert_eq!(c.deltas.len(), 1);
+
+        // Renting and buying share the checking account, and see the same market
+        let delta = &c.deltas[0];
+        assert_eq!(delta.accounts.len(), 1);
+        assert_eq!(delta.accounts[0].account, "Checking");
+        assert_eq!(
+            delta.net_worth.len(),
+            c.results[0]["Checking"].balances.len()
+        );
+
+        // Net worth deltas add up the shared accounts and the mortgage only buying has
+        let first = &delta.net_worth[0];
+        let mortgage = c.results[1]["Mortgage"].balances[0].balance;
+        let checking = delta.accounts[0].deltas[0].mean;
+        assert!((first.delta - (mortgage + checking)).abs() < 1e-6);
+
+        // Account deltas are kept on reporting dates, where there are percentiles to compare
+        let percentiles = &c.results[0]["Checking"].percentiles;
+        assert!(percentiles.len() < c.results[0]["Checking"].balances.len());
+        assert_eq!(delta.accounts[0].deltas.len(), percentiles.len());
+
+        // The same scenario compared to itself has no deltas
+        let c = compare(&[renting.clone(), renting], Some(7)).unwrap();
+        assert_eq!(c.seed, 7);
+        assert!(c.deltas[0].diff.is_empty());
+        assert!(c.deltas[0].net_worth.iter().all(|d| d.delta == 0.0));
+    }
+}
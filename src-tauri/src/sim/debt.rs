@@ -0,0 +1,357 @@
+use super::cash::{CashFlow, Frequency};
+use super::loan::{monthly_payment, Loan};
+use crate::error::Error;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Give up on plans that have not paid off every debt after this many months.
+const MAX_PLAN_MONTHS: u32 = 1200;
+
+/// The order in which a debt budget beyond the minimum payments is applied.
+///
+/// `Avalanche` targets the highest interest rate first, `Snowball` the smallest balance first,
+/// and `Custom` follows the given list of loan names.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum PayoffStrategy {
+    Avalanche,
+    Snowball,
+    Custom(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct DebtPayoff {
+    pub name: String,
+    pub payoff_date: NaiveDate,
+    pub total_interest: f64,
+    pub total_paid: f64,
+}
+
+/// The outcome of applying a `PayoffStrategy` to a set of loans.
+///
+/// `loans` are copies of the input loans with the extra principal chosen by the planner added
+/// to their `extra_payments`, so they can be placed in a `Scenario` to post the plan against
+/// their funding accounts.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct DebtPayoffPlan {
+    pub strategy: PayoffStrategy,
+    pub monthly_budget: f64,
+    pub payoff_date: NaiveDate,
+    pub total_interest: f64,
+    pub debts: Vec<DebtPayoff>,
+    pub loans: Vec<Loan>,
+}
+
+struct DebtState {
+    loan: Loan,
+    balance: f64,
+    rate: f64,
+    payment: f64,
+    prev_date: NaiveDate,
+    total_interest: f64,
+    total_paid: f64,
+    payoff_date: Option<NaiveDate>,
+}
+
+impl DebtState {
+    fn new(loan: &Loan) -> DebtState {
+        let rate = loan.rate_at(loan.start_date);
+        DebtState {
+            loan: loan.clone(),
+            balance: loan.principal,
+            rate,
+            payment: monthly_payment(loan.principal, rate, loan.term_months),
+            prev_date: loan.start_date,
+            total_interest: 0.0,
+            total_paid: 0.0,
+            payoff_date: None,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.payoff_date.is_none()
+    }
+
+    fn pay(&mut self, amount: f64, date: NaiveDate) {
+        self.balance -= amount;
+        self.total_paid += amount;
+        if self.balance <= 0.005 {
+            self.balance = 0.0;
+            self.payoff_date = Some(date);
+        }
+    }
+}
+
+fn month_index(d: NaiveDate) -> i32 {
+    d.year() * 12 + d.month0() as i32
+}
+
+/// Simulates paying down `loans` with a fixed `monthly_budget`.
+///
+/// Every month each loan receives its scheduled payment, and whatever is left of the budget is
+/// applied as extra principal in the order given by `strategy`. Payments freed up by a paid-off
+/// loan roll forward to the next one in line. Extra payments already on a loan are made as
+/// scheduled, on top of the budget.
+pub fn plan_debt_payoff(
+    loans: &[Loan],
+    monthly_budget: f64,
+    strategy: &PayoffStrategy,
+) -> Result<DebtPayoffPlan, Error> {
+    if loans.is_empty() {
+        return Err(Error::Validation("No loans to plan".to_string()));
+    }
+    if let PayoffStrategy::Custom(order) = strategy {
+        for name in order {
+            if !loans.iter().any(|l| &l.name == name) {
+                return Err(Error::Validation(format!(
+                    "Unknown loan in payoff order: {}",
+                    name
+                )));
+            }
+        }
+    }
+
+    let mut debts: Vec<DebtState> = loans.iter().map(DebtState::new).collect();
+    let first_month = loans
+        .iter()
+        .map(|l| month_index(l.start_date) + 1)
+        .min()
+        .unwrap();
+
+    for m in first_month..first_month + MAX_PLAN_MONTHS as i32 {
+        if debts.iter().all(|d| !d.is_open()) {
+            break;
+        }
+
+        // Accrue interest and make the scheduled payment on every loan due this month
+        let mut due: Vec<(usize, NaiveDate)> = vec![];
+        let mut budget = monthly_budget;
+        for (i, debt) in debts.iter_mut().enumerate() {
+            let n = m - month_index(debt.loan.start_date);
+            if !debt.is_open() || n < 1 {
+                continue;
+            }
+            let n = n as u32;
+            let date = debt.loan.payment_date(n)?;
+            let prev_date = debt.prev_date;
+
+            let rate = debt.loan.rate_at(debt.prev_date);
+            if rate != debt.rate {
+                debt.rate = rate;
+                let remaining = debt.loan.term_months.saturating_sub(n - 1).max(1);
+                debt.payment = monthly_payment(debt.balance, rate, remaining);
+            }
+
+            let interest = debt.balance * debt.rate / 12.0;
+            debt.total_interest += interest;
+            debt.balance += interest;
+
+            let scheduled = if n >= debt.loan.term_months {
+                debt.balance
+            } else {
+                debt.payment.min(debt.balance)
+            };
+            debt.pay(scheduled, date);
+            budget -= scheduled;
+            if debt.is_open() {
+                let extra = debt
+                    .loan
+                    .extra_principal_between(prev_date, date)
+                    .max(0.0)
+                    .min(debt.balance);
+                debt.pay(extra, date);
+            }
+            debt.prev_date = date;
+            due.push((i, date));
+        }
+
+        if budget < -0.005 {
+            return Err(Error::Validation(format!(
+                "Monthly budget of {:.2} does not cover the minimum payments",
+                monthly_budget
+            )));
+        }
+
+        // Apply what is left of the budget to the highest priority open loans
+        for i in priority(&debts, strategy) {
+            if budget <= 0.005 {
+                break;
+            }
+            let date = match due.iter().find(|(j, _)| *j == i) {
+                Some((_, date)) => *date,
+                None => continue,
+            };
+            let debt = &mut debts[i];
+            if !debt.is_open() {
+                continue;
+            }
+            let extra = budget.min(debt.balance);
+            budget -= extra;
+            debt.pay(extra, date);
+            debt.loan.add_extra_payment(CashFlow::new(
+                Some(format!("{} Extra Principal", debt.loan.name)),
+                extra,
+                Some(Frequency::Once),
+                Some(date),
+                Some(date),
+                None,
+                Some(vec!["Debt".to_string()]),
+            ));
+        }
+    }
+
+    if debts.iter().any(|d| d.is_open()) {
+        return Err(Error::Simulation(format!(
+            "Debts are not paid off within {} months",
+            MAX_PLAN_MONTHS
+        )));
+    }
+
+    let payoffs: Vec<DebtPayoff> = debts
+        .iter()
+        .map(|d| DebtPayoff {
+            name: d.loan.name.clone(),
+            payoff_date: d.payoff_date.unwrap(),
+            total_interest: d.total_interest,
+            total_paid: d.total_paid,
+        })
+        .collect();
+
+    Ok(DebtPayoffPlan {
+        strategy: strategy.clone(),
+        monthly_budget,
+        payoff_date: payoffs.iter().map(|p| p.payoff_date).max().unwrap(),
+        total_interest: payoffs.iter().fold(0.0, |acc, p| acc + p.total_interest),
+        debts: payoffs,
+        loans: debts.into_iter().map(|d| d.loan).collect(),
+    })
+}
+
+/// Runs `plan_debt_payoff` once per strategy so the outcomes can be compared side by side.
+pub fn compare_strategies(
+    loans: &[Loan],
+    monthly_budget: f64,
+    strategies: &[PayoffStrategy],
+) -> Result<Vec<DebtPayoffPlan>, Error> {
+    strategies
+        .iter()
+        .map(|s| plan_debt_payoff(loans, monthly_budget, s))
+        .collect()
+}
+
+fn priority(debts: &[DebtState], strategy: &PayoffStrategy) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..debts.len()).filter(|i| debts[*i].is_open()).collect();
+    match strategy {
+        PayoffStrategy::Avalanche => order.sort_by(|a, b| {
+            debts[*b]
+                .rate
+                .total_cmp(&debts[*a].rate)
+                .then(debts[*a].balance.total_cmp(&debts[*b].balance))
+        }),
+        PayoffStrategy::Snowball => order.sort_by(|a, b| {
+            debts[*a]
+                .balance
+                .total_cmp(&debts[*b].balance)
+                .then(debts[*b].rate.total_cmp(&debts[*a].rate))
+        }),
+        PayoffStrategy::Custom(names) => order.sort_by_key(|i| {
+            names
+                .iter()
+                .position(|n| n == &debts[*i].loan.name)
+                .unwrap_or(names.len())
+        }),
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debts() -> Vec<Loan> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        vec![
+            Loan::new("Car".to_string(), 15_000.0, 0.06, 60, start, None),
+            Loan::new("Credit Card".to_string(), 8_000.0, 0.24, 120, start, None),
+            Loan::new("Student Loan".to_string(), 3_000.0, 0.04, 120, start, None),
+        ]
+    }
+
+    #[test]
+    fn test_avalanche_pays_less_interest_than_snowball() {
+        let plans = compare_strategies(
+            &debts(),
+            1_000.0,
+            &[PayoffStrategy::Avalanche, PayoffStrategy::Snowball],
+        )
+        .unwrap();
+
+        assert!(plans[0].total_interest < plans[1].total_interest);
+
+        // Snowball clears the smallest balance first
+        let snowball = &plans[1];
+        let student = snowball.debts.iter().find(|d| d.name == "Student Loan");
+        assert!(snowball
+            .debts
+            .iter()
+            .all(|d| d.payoff_date >= student.unwrap().payoff_date));
+    }
+
+    #[test]
+    fn test_plan_beats_minimum_payments() {
+        let loans = debts();
+        let plan = plan_debt_payoff(&loans, 1_000.0, &PayoffStrategy::Avalanche).unwrap();
+        let minimum_interest = loans
+            .iter()
+            .fold(0.0, |acc, l| acc + l.total_interest().unwrap());
+
+        assert!(plan.total_interest < minimum_interest);
+        assert!(plan.payoff_date < NaiveDate::from_ymd_opt(2034, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_planned_loans_match_plan() {
+        let plan = plan_debt_payoff(
+            &debts(),
+            1_000.0,
+            &PayoffStrategy::Custom(vec!["Car".to_string()]),
+        )
+        .unwrap();
+
+        for (loan, payoff) in plan.loans.iter().zip(&plan.debts) {
+            assert_eq!(loan.payoff_date().unwrap(), Some(payoff.payoff_date));
+            assert!((loan.total_interest().unwrap() - payoff.total_interest).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_plan_keeps_existing_extra_payments() {
+        let mut loans = debts();
+        loans[0].add_extra_payment(CashFlow::new(
+            Some("Car Bonus Payment".to_string()),
+            2_000.0,
+            Some(Frequency::Once),
+            Some(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()),
+            Some(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()),
+            None,
+            None,
+        ));
+        let without = plan_debt_payoff(&debts(), 1_000.0, &PayoffStrategy::Avalanche).unwrap();
+        let plan = plan_debt_payoff(&loans, 1_000.0, &PayoffStrategy::Avalanche).unwrap();
+        assert!(plan.total_interest < without.total_interest);
+
+        for (loan, payoff) in plan.loans.iter().zip(&plan.debts) {
+            assert_eq!(loan.payoff_date().unwrap(), Some(payoff.payoff_date));
+            assert!((loan.total_interest().unwrap() - payoff.total_interest).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_budget_must_cover_minimums() {
+        let r = plan_debt_payoff(&debts(), 100.0, &PayoffStrategy::Snowball);
+        assert!(r.is_err());
+    }
+}
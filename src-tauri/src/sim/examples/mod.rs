@@ -1,2 +1,3 @@
+pub mod mortgage_scenario;
 pub mod simple_account;
 pub mod two_account_scenario;
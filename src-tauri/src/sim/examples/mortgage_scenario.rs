@@ -0,0 +1,40 @@
+use super::simple_account::simple_account;
+use crate::sim::historical::MarketModel;
+use crate::sim::loan::Loan;
+use crate::sim::{InvestedAccount, Scenario};
+
+pub fn mortgage_scenario() -> Scenario {
+    let mut checking = simple_account();
+    checking.set_name("Checking".into());
+    checking
+        .cash_flows
+        .retain(|cf| cf.name != Some("Mortgage".to_string()));
+
+    let mortgage = Loan::new(
+        "Mortgage".into(),
+        500_000.0,
+        0.065,
+        360,
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        Some("Checking".into()),
+    );
+
+    Scenario {
+        accounts: vec![InvestedAccount {
+            account: checking,
+            portfolio: None,
+        }],
+        start_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        end_date: chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        transfers: vec![],
+        loans: vec![mortgage],
+        withdrawals: vec![],
+        goals: vec![],
+        household: None,
+        longevity: None,
+        market: MarketModel::Random,
+        num_samples: 1,
+        inflation: 0.0,
+        seed: None,
+    }
+}
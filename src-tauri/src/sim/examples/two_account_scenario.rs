@@ -1,9 +1,11 @@
 use super::simple_account::simple_account;
+use crate::sim::historical::MarketModel;
 use crate::sim::{InvestedAccount, Scenario};
 
 pub fn two_account_scenario() -> Scenario {
     let account1 = simple_account();
-    let account2 = simple_account();
+    let mut account2 = simple_account();
+    account2.set_name("Example 2".to_string());
     Scenario {
         accounts: vec![
             InvestedAccount {
@@ -18,6 +20,14 @@ pub fn two_account_scenario() -> Scenario {
         start_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         end_date: chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
         transfers: vec![],
+        loans: vec![],
+        withdrawals: vec![],
+        goals: vec![],
+        household: None,
+        longevity: None,
+        market: MarketModel::Random,
         num_samples: 1,
+        inflation: 0.0,
+        seed: None,
     }
 }
@@ -0,0 +1,222 @@
+use super::cash::{CashFlow, Frequency};
+use super::metrics::{quantile, Metric};
+use super::solver::{bisect, SolveFor, DEFAULT_SEED};
+use super::validation::Problem;
+use super::{simulate, Scenario, SimulationResult};
+use crate::error::Error;
+use chrono::NaiveDate;
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+// Tried in turn as the upper bound of the search, as multiples of the monthly contribution
+// that would save the whole target without any growth
+const CONTRIBUTION_BOUNDS: [f64; 3] = [2.0, 4.0, 8.0];
+// Contributions are solved to within a dollar a month
+const CONTRIBUTION_TOLERANCE: f64 = 12.0;
+
+/// A savings target, such as a house down payment or college fund, that the combined balance of
+/// `accounts` should reach by `date`.
+///
+/// `success_rate` is the probability of reaching the target the plan should aim for, and is used
+/// to size the extra contribution reported in `GoalResult`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Goal {
+    pub name: String,
+    pub target: f64,
+    pub date: NaiveDate,
+    pub accounts: Vec<String>,
+    pub success_rate: f64,
+}
+
+impl Goal {
+    pub fn new(name: String, target: f64, date: NaiveDate, accounts: Vec<String>) -> Goal {
+        Goal {
+            name,
+            target,
+            date,
+            accounts,
+            success_rate: 0.9,
+        }
+    }
+
+    /// Every problem with the goal itself. Its date and accounts are checked against the
+    /// scenario in `Scenario::validate`.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        if !self.target.is_finite() {
+            problems.push(Problem::new("target", "is not a number"));
+        }
+        if self.accounts.is_empty() {
+            problems.push(Problem::new("accounts", "is empty"));
+        }
+        if !(0.0..=1.0).contains(&self.success_rate) {
+            problems.push(Problem::new("success_rate", "must be between 0 and 1"));
+        }
+        problems
+    }
+}
+
+/// Funding status of a `Goal` across the simulated paths.
+///
+/// - `probability` is the fraction of paths on which the target is reached.
+/// - `median_shortfall` is the median amount missing on the paths that fall short.
+/// - `required_monthly_contribution` is the extra amount to save each month, into the first of the
+///   goal's accounts from the start of the scenario until the goal date, for the target to be
+///   reached on `success_rate` of paths. It is found by simulating the scenario again with the
+///   contribution as a cash flow, so the extra savings earn the account's returns. It is `0` for
+///   goals already funded at `success_rate`, and `None` when no contribution that saves up to
+///   eight times the target is enough.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct GoalResult {
+    pub goal: Goal,
+    pub probability: f64,
+    pub median_shortfall: f64,
+    pub required_monthly_contribution: Option<f64>,
+}
+
+impl GoalResult {
+    /// Evaluates `goal` from the combined balance on each path at the goal date. The required
+    /// contribution is left for `size_contributions` unless the goal is already funded.
+    pub fn new(goal: &Goal, balances: &Array1<f64>) -> GoalResult {
+        let shortfalls: Vec<f64> = balances.iter().map(|b| goal.target - b).collect();
+        let missed: Vec<f64> = shortfalls.iter().copied().filter(|s| *s > 0.0).collect();
+        let probability = 1.0 - missed.len() as f64 / balances.len() as f64;
+        let median_shortfall = if missed.is_empty() {
+            0.0
+        } else {
+            quantile(&missed, 0.5)
+        };
+
+        // The shortfall that `success_rate` of paths stay within
+        let funded = quantile(&shortfalls, goal.success_rate) <= 0.0;
+
+        GoalResult {
+            goal: goal.clone(),
+            probability,
+            median_shortfall,
+            required_monthly_contribution: funded.then_some(0.0),
+        }
+    }
+}
+
+/// Solves the required contribution of every goal in `results` that isn't already funded.
+pub(crate) fn size_contributions(
+    scenario: &Scenario,
+    results: &mut HashMap<String, SimulationResult>,
+) -> Result<(), Error> {
+    for goal in &scenario.goals {
+        let r = &results[&goal.accounts[0]];
+        let goal_result = r.goals.iter().find(|g| g.goal.name == goal.name).unwrap();
+        if goal_result.required_monthly_contribution.is_some() {
+            continue;
+        }
+        let contribution = required_monthly_contribution(scenario, goal)?;
+        for a in &goal.accounts {
+            for g in results.get_mut(a).unwrap().goals.iter_mut() {
+                if g.goal.name == goal.name {
+                    g.required_monthly_contribution = contribution;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The monthly contribution at which `goal` is reached on `success_rate` of paths, searched by
+/// bisection with `simulate` between nothing and a multiple of the contribution that would save
+/// the whole target without growth.
+fn required_monthly_contribution(scenario: &Scenario, goal: &Goal) -> Result<Option<f64>, Error> {
+    let name = format!("{} contribution", goal.name);
+    let mut scenario = scenario.clone();
+    scenario.seed.get_or_insert(DEFAULT_SEED);
+    scenario.goals = vec![goal.clone()];
+    let account = scenario
+        .accounts
+        .iter_mut()
+        .find(|a| a.account.name == goal.accounts[0])
+        .unwrap();
+    account.account.cash_flows.push(CashFlow::new(
+        Some(name.clone()),
+        0.0,
+        Some(Frequency::MonthStart),
+        Some(scenario.start_date),
+        Some(goal.date),
+        None,
+        Some(vec!["Goal".to_string()]),
+    ));
+
+    let variable = SolveFor::CashFlowAmount {
+        account: goal.accounts[0].clone(),
+        cash_flow: name,
+    };
+    let metric = Metric::GoalProbability {
+        goal: goal.name.clone(),
+    };
+    let evaluate = |annual: f64| -> Result<(f64, ()), Error> {
+        let results = simulate(variable.apply(&scenario, annual)?)?;
+        Ok((metric.evaluate(&results)?, ()))
+    };
+
+    if evaluate(0.0)?.0 >= goal.success_rate {
+        return Ok(Some(0.0));
+    }
+    let months = (goal.date - scenario.start_date).num_days() as f64 * 12.0 / 365.25;
+    let unfunded = goal.target.max(0.0) * 12.0 / months.max(1.0);
+    for multiple in CONTRIBUTION_BOUNDS {
+        let upper = unfunded * multiple;
+        if evaluate(upper)?.0 < goal.success_rate {
+            continue;
+        }
+        let (annual, _, _, _) = bisect(
+            evaluate,
+            goal.success_rate,
+            0.0,
+            upper,
+            CONTRIBUTION_TOLERANCE,
+            |x| x,
+        )?;
+        return Ok(Some(annual / 12.0));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal() -> Goal {
+        Goal::new(
+            "House".to_string(),
+            80_000.0,
+            NaiveDate::from_ymd_opt(2027, 6, 1).unwrap(),
+            vec!["Savings".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_goal_result() {
+        let balances = Array1::from_vec(vec![
+            50_000.0, 60_000.0, 70_000.0, 75_000.0, 80_000.0, 85_000.0, 90_000.0, 95_000.0,
+            100_000.0, 110_000.0, 120_000.0,
+        ]);
+        let r = GoalResult::new(&goal(), &balances);
+        assert!((r.probability - 7.0 / 11.0).abs() < 1e-9);
+        assert_eq!(r.median_shortfall, 15_000.0);
+
+        // Fewer than 90% of paths reach the target, so the contribution is left to be solved
+        assert_eq!(r.required_monthly_contribution, None);
+    }
+
+    #[test]
+    fn test_goal_already_funded() {
+        let balances = Array1::from_vec(vec![90_000.0, 100_000.0]);
+        let r = GoalResult::new(&goal(), &balances);
+        assert_eq!(r.probability, 1.0);
+        assert_eq!(r.median_shortfall, 0.0);
+        assert_eq!(r.required_monthly_contribution, Some(0.0));
+    }
+}
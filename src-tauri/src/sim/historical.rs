@@ -0,0 +1,332 @@
+use super::metrics::Metric;
+use super::portfolio::Portfolio;
+use super::{goal, simulate, Scenario, SimulationResult};
+use crate::error::Error;
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// Monthly historical returns for a set of assets, such as a stock and a bond index.
+///
+/// `returns[i][j]` is the return of `assets[j]` in the `i`th month after `start`, as a fraction
+/// (e.g. `-0.17` for the S&P 500 in October 2008). Datasets are read from CSV files with a
+/// `month` column of `YYYY-MM` dates followed by one column per asset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct ReturnsDataset {
+    pub name: String,
+    pub start: NaiveDate,
+    pub assets: Vec<String>,
+    pub returns: Vec<Vec<f64>>,
+}
+
+impl ReturnsDataset {
+    pub fn from_csv(name: &str, csv: &str) -> Result<ReturnsDataset, Error> {
+        let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or(Error::Parse("Returns dataset is empty".to_string()))?;
+        let assets: Vec<String> = header
+            .split(',')
+            .skip(1)
+            .map(|a| a.trim().to_string())
+            .collect();
+
+        let mut start: Option<NaiveDate> = None;
+        let mut returns = vec![];
+        for (i, line) in lines.enumerate() {
+            let mut fields = line.split(',').map(str::trim);
+            let month = fields.next().unwrap();
+            let date = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+                .map_err(|_| Error::Parse(format!("Invalid month on line {}: {}", i + 2, month)))?;
+            let expected = start.map(|s| s + Months::new(i as u32));
+            if expected.is_some_and(|e| e != date) {
+                return Err(Error::Parse(format!(
+                    "Months are not consecutive on line {}",
+                    i + 2
+                )));
+            }
+            start.get_or_insert(date);
+
+            let row = fields
+                .map(|r| r.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| Error::Parse(format!("Invalid return on line {}", i + 2)))?;
+            if row.len() != assets.len() {
+                return Err(Error::Parse(format!(
+                    "Wrong number of returns on line {}",
+                    i + 2
+                )));
+            }
+            returns.push(row);
+        }
+
+        Ok(ReturnsDataset {
+            name: name.to_string(),
+            start: start.ok_or(Error::Parse("Returns dataset has no returns".to_string()))?,
+            assets,
+            returns,
+        })
+    }
+
+    /// The first month after the last one in the dataset.
+    pub fn end(&self) -> NaiveDate {
+        self.start + Months::new(self.returns.len() as u32)
+    }
+}
+
+/// Replays the months of `dataset` from `window_start` onwards in place of random returns,
+/// starting on the simulation date `from`. Every path sees the same returns, with each month's
+/// return spread evenly across its days.
+///
+/// Portfolio assets are matched to dataset assets by name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct HistoricalReplay {
+    pub dataset: ReturnsDataset,
+    pub window_start: NaiveDate,
+    pub from: NaiveDate,
+}
+
+impl HistoricalReplay {
+    fn month_index(&self, d: &NaiveDate) -> usize {
+        let months = |d: &NaiveDate| d.year() * 12 + d.month0() as i32;
+        (months(d) - months(&self.from) + months(&self.window_start) - months(&self.dataset.start))
+            as usize
+    }
+
+    /// Checks that the dataset covers every month from `from` through `end_date` and every
+    /// asset in `portfolios`.
+    pub fn validate<'a>(
+        &self,
+        end_date: NaiveDate,
+        portfolios: impl Iterator<Item = &'a Portfolio>,
+    ) -> Result<(), Error> {
+        let start = NaiveDate::from_ymd_opt(self.window_start.year(), self.window_start.month(), 1)
+            .unwrap();
+        if start < self.dataset.start || self.month_index(&end_date) >= self.dataset.returns.len() {
+            return Err(Error::Validation(format!(
+                "Returns dataset {} does not cover the replay",
+                self.dataset.name
+            )));
+        }
+        for asset in portfolios.flat_map(|p| &p.assets) {
+            if !self.dataset.assets.contains(&asset.name) {
+                return Err(Error::Validation(format!(
+                    "Returns dataset {} has no returns for {}",
+                    self.dataset.name, asset.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The return of `portfolio` over the single day `d`, or `None` before the replay starts.
+    pub fn daily_return(&self, portfolio: &Portfolio, d: &NaiveDate) -> Option<f64> {
+        if *d < self.from {
+            return None;
+        }
+        let month = &self.dataset.returns[self.month_index(d)];
+        let next_month = NaiveDate::from_ymd_opt(d.year(), d.month(), 1).unwrap() + Months::new(1);
+        let days = next_month.pred_opt().unwrap().day() as f64;
+
+        let mut ret = 0.0;
+        for (asset, weight) in portfolio.assets.iter().zip(&portfolio.weights) {
+            let j = self.dataset.assets.iter().position(|a| a == &asset.name)?;
+            ret += weight * ((1.0 + month[j]).powf(1.0 / days) - 1.0);
+        }
+        Some(ret)
+    }
+}
+
+/// How asset returns are generated. `Random` draws them from each asset's mean and standard
+/// deviation; `Historical` replays a window of a returns dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum MarketModel {
+    #[default]
+    Random,
+    Historical(HistoricalReplay),
+}
+
+/// The outcome of replaying history from the start of `window_start`'s year.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct ReplayOutcome {
+    pub window_start: NaiveDate,
+    pub metric: f64,
+}
+
+/// The result of a rolling replay: the `metric` for every historical start year, and the full
+/// results of the worst and median years.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct RollingReplay {
+    pub metric: Metric,
+    pub outcomes: Vec<ReplayOutcome>,
+    pub worst: ReplayOutcome,
+    pub worst_results: HashMap<String, SimulationResult>,
+    pub median: ReplayOutcome,
+    pub median_results: HashMap<String, SimulationResult>,
+}
+
+/// Replays `dataset` from every January with enough history to cover `scenario`, starting on the
+/// scenario's start date, and ranks the outcomes by `metric`.
+pub fn rolling_replay(
+    scenario: &Scenario,
+    dataset: &ReturnsDataset,
+    metric: &Metric,
+) -> Result<RollingReplay, Error> {
+    let replay = |window_start: NaiveDate| HistoricalReplay {
+        dataset: dataset.clone(),
+        window_start,
+        from: scenario.start_date,
+    };
+    let first_year = dataset.start.year() + i32::from(dataset.start.month() != 1);
+    let starts: Vec<NaiveDate> = (first_year..dataset.end().year())
+        .map(|y| NaiveDate::from_ymd_opt(y, 1, 1).unwrap())
+        .filter(|start| {
+            replay(*start)
+                .validate(scenario.end_date, std::iter::empty())
+                .is_ok()
+        })
+        .collect();
+    if starts.is_empty() {
+        return Err(Error::Validation(format!(
+            "Returns dataset {} is too short for the scenario",
+            dataset.name
+        )));
+    }
+
+    let replay_scenario = |window_start: NaiveDate| {
+        let mut s = scenario.clone();
+        s.market = MarketModel::Historical(replay(window_start));
+        s
+    };
+    let mut runs = vec![];
+    for start in starts {
+        let results = simulate(replay_scenario(start))?;
+        let outcome = ReplayOutcome {
+            window_start: start,
+            metric: metric.evaluate(&results)?,
+        };
+        runs.push((outcome, results));
+    }
+    let outcomes: Vec<ReplayOutcome> = runs.iter().map(|(o, _)| o.clone()).collect();
+
+    runs.sort_by(|a, b| a.0.metric.total_cmp(&b.0.metric));
+    // Goal contributions are solved by bisection too, so only for the windows that are reported
+    let (median, mut median_results) = runs[(runs.len() - 1) / 2].clone();
+    let (worst, mut worst_results) = runs.swap_remove(0);
+    for (outcome, results) in [(&median, &mut median_results), (&worst, &mut worst_results)] {
+        goal::size_contributions(&replay_scenario(outcome.window_start), results)?;
+    }
+    Ok(RollingReplay {
+        metric: metric.clone(),
+        outcomes,
+        worst,
+        worst_results,
+        median,
+        median_results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::portfolio::Asset;
+    use crate::sim::run_simulation;
+
+    const CSV: &str = "month,Stocks,Bonds
+2007-11,-0.04,0.01
+2007-12,-0.01,0.00
+2008-01,-0.06,0.01
+2008-02,-0.03,0.00
+2008-03,-0.01,0.00
+";
+
+    fn dataset(years: i32) -> ReturnsDataset {
+        // Steady 1% monthly stock returns, except for a crash through 2008
+        let mut csv = "month,Stocks\n".to_string();
+        for y in 2000..2000 + years {
+            for m in 1..=12 {
+                let r = if y == 2008 { -0.03 } else { 0.01 };
+                csv += &format!("{}-{:02},{}\n", y, m, r);
+            }
+        }
+        ReturnsDataset::from_csv("Test", &csv).unwrap()
+    }
+
+    fn scenario() -> Scenario {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario.accounts[0].account.cash_flows = vec![];
+        scenario.accounts[0].portfolio = Some(Portfolio::new(
+            vec![Asset::new("Stocks".to_string(), 0.07, 0.15)],
+            vec![1.0],
+        ));
+        scenario.num_samples = 3;
+        scenario
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let d = ReturnsDataset::from_csv("Crisis", CSV).unwrap();
+        assert_eq!(d.start, NaiveDate::from_ymd_opt(2007, 11, 1).unwrap());
+        assert_eq!(d.end(), NaiveDate::from_ymd_opt(2008, 4, 1).unwrap());
+        assert_eq!(d.assets, vec!["Stocks".to_string(), "Bonds".to_string()]);
+        assert_eq!(d.returns[2], vec![-0.06, 0.01]);
+
+        assert!(ReturnsDataset::from_csv("Gap", "month,Stocks\n2008-01,0.1\n2008-03,0.1").is_err());
+        assert!(ReturnsDataset::from_csv("Short", "month,Stocks,Bonds\n2008-01,0.1").is_err());
+    }
+
+    #[test]
+    fn test_historical_replay() {
+        let mut scenario = scenario();
+        scenario.market = MarketModel::Historical(HistoricalReplay {
+            dataset: dataset(10),
+            window_start: NaiveDate::from_ymd_opt(2008, 1, 1).unwrap(),
+            from: scenario.start_date,
+        });
+        let r = run_simulation(scenario.clone()).unwrap();
+
+        // Every path replays the same crash
+        let expected = 10_000.0 * 0.97f64.powi(12);
+        assert!(r["Example"]
+            .terminal_balances
+            .iter()
+            .all(|b| (b - expected).abs() < 1e-6));
+
+        // The dataset has to cover the whole replay, and every asset
+        if let MarketModel::Historical(h) = &mut scenario.market {
+            h.window_start = NaiveDate::from_ymd_opt(2009, 6, 1).unwrap();
+        }
+        assert!(run_simulation(scenario.clone()).is_err());
+        scenario.accounts[0].portfolio = Portfolio::default();
+        assert!(run_simulation(scenario).is_err());
+    }
+
+    #[test]
+    fn test_rolling_replay() {
+        let metric = Metric::TerminalBalance {
+            account: "Example".to_string(),
+            percentile: 0.5,
+        };
+        let r = rolling_replay(&scenario(), &dataset(10), &metric).unwrap();
+        assert_eq!(r.outcomes.len(), 10);
+        assert_eq!(
+            r.worst.window_start,
+            NaiveDate::from_ymd_opt(2008, 1, 1).unwrap()
+        );
+        assert!((r.median.metric - 10_000.0 * 1.01f64.powi(12)).abs() < 1e-6);
+        assert_eq!(
+            r.worst_results["Example"].terminal_balances[0],
+            r.worst.metric
+        );
+
+        let short = ReturnsDataset::from_csv("Crisis", CSV).unwrap();
+        assert!(rolling_replay(&scenario(), &short, &metric).is_err());
+    }
+}
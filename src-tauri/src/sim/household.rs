@@ -0,0 +1,390 @@
+use super::cash::{Account, CashFlow};
+use super::mortality::BundledTable;
+use crate::error::Error;
+use chrono::{Datelike, Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A member of a `Household`.
+///
+/// `life_expectancy` is the age, in years, the person is assumed to live to. When the scenario
+/// has stochastic `Longevity`, a bundled `mortality_table` is used instead to sample a death
+/// date on each path.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Person {
+    pub name: String,
+    pub birth_date: NaiveDate,
+    pub retirement_date: Option<NaiveDate>,
+    pub life_expectancy: Option<u32>,
+    #[serde(default)]
+    pub mortality_table: Option<BundledTable>,
+}
+
+impl Person {
+    pub fn new(name: String, birth_date: NaiveDate) -> Person {
+        Person {
+            name,
+            birth_date,
+            retirement_date: None,
+            life_expectancy: None,
+            mortality_table: None,
+        }
+    }
+
+    /// The date this person turns `years` old.
+    pub fn birthday(&self, years: u32) -> Result<NaiveDate, Error> {
+        years
+            .checked_mul(12)
+            .and_then(|months| self.birth_date.checked_add_months(Months::new(months)))
+            .ok_or(Error::Validation(format!(
+                "{} turns {} too far in the future",
+                self.name, years
+            )))
+    }
+
+    pub fn age_on(&self, date: NaiveDate) -> u32 {
+        let mut age = date.year() - self.birth_date.year();
+        if (date.month(), date.day()) < (self.birth_date.month(), self.birth_date.day()) {
+            age -= 1;
+        }
+        age.max(0) as u32
+    }
+
+    pub fn death_date(&self) -> Result<Option<NaiveDate>, Error> {
+        self.life_expectancy
+            .map(|years| self.birthday(years))
+            .transpose()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum LifeEventKind {
+    Retirement,
+    Age(u32),
+    Death,
+}
+
+/// A point in a household member's life that a `CashFlow` can start or end on, such as "at
+/// retirement" or "when the child turns 18".
+///
+/// `person` defaults to the owner of the account the cash flow belongs to. The event date is
+/// shifted by `offset_months`, so that e.g. a flow ending the month before retirement can be
+/// expressed with an offset of `-1`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct LifeEvent {
+    pub person: Option<String>,
+    pub kind: LifeEventKind,
+    #[serde(default)]
+    pub offset_months: i32,
+}
+
+impl LifeEvent {
+    pub fn new(person: Option<String>, kind: LifeEventKind) -> LifeEvent {
+        LifeEvent {
+            person,
+            kind,
+            offset_months: 0,
+        }
+    }
+}
+
+/// The people a `Scenario` plans for. `Account`s are linked to a member through their `owner`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Household {
+    pub members: Vec<Person>,
+}
+
+impl Household {
+    pub fn new(members: Vec<Person>) -> Household {
+        Household { members }
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Person> {
+        self.members.iter().find(|p| p.name == name)
+    }
+
+    fn event_person(&self, event: &LifeEvent, owner: Option<&str>) -> Result<&Person, Error> {
+        let name = match (&event.person, owner) {
+            (Some(person), _) => person.as_str(),
+            (None, Some(owner)) => owner,
+            (None, None) => {
+                return Err(Error::Validation(
+                    "Life event has no person and the account has no owner".to_string(),
+                ))
+            }
+        };
+        self.member(name).ok_or(Error::Validation(format!(
+            "Unknown household member: {}",
+            name
+        )))
+    }
+
+    /// The date of `event`, given the dates a person dies on. Returns `None` if the event never
+    /// happens, e.g. a death for someone without a life expectancy.
+    pub fn event_date_with(
+        &self,
+        event: &LifeEvent,
+        owner: Option<&str>,
+        death_date: impl Fn(&Person) -> Result<Option<NaiveDate>, Error>,
+    ) -> Result<Option<NaiveDate>, Error> {
+        let person = self.event_person(event, owner)?;
+        let date = match event.kind {
+            LifeEventKind::Retirement => person.retirement_date,
+            LifeEventKind::Age(years) => Some(person.birthday(years)?),
+            LifeEventKind::Death => death_date(person)?,
+        };
+        date.map(|d| offset(d, event.offset_months)).transpose()
+    }
+
+    pub fn event_date(
+        &self,
+        event: &LifeEvent,
+        owner: Option<&str>,
+    ) -> Result<Option<NaiveDate>, Error> {
+        self.event_date_with(event, owner, Person::death_date)
+    }
+
+    /// Replaces the life events on `cash_flow` with the dates they resolve to, given the dates
+    /// people die on. A flow runs from the later of its start date and start event to the earlier
+    /// of its end date and end event.
+    pub fn resolve_cash_flow_with(
+        &self,
+        cash_flow: &CashFlow,
+        owner: Option<&str>,
+        death_date: impl Fn(&Person) -> Result<Option<NaiveDate>, Error>,
+    ) -> Result<CashFlow, Error> {
+        let mut resolved = cash_flow.clone();
+        if let Some(event) = &cash_flow.start_event {
+            // A flow that starts on an event that never happens never starts
+            let start = self
+                .event_date_with(event, owner, &death_date)?
+                .unwrap_or(NaiveDate::MAX);
+            resolved.start_date = Some(cash_flow.start_date.map_or(start, |s| s.max(start)));
+            resolved.start_event = None;
+        }
+        if let Some(event) = &cash_flow.end_event {
+            if let Some(end) = self.event_date_with(event, owner, &death_date)? {
+                resolved.end_date = Some(cash_flow.end_date.map_or(end, |e| e.min(end)));
+            }
+            resolved.end_event = None;
+        }
+        Ok(resolved)
+    }
+
+    pub fn resolve_cash_flow(
+        &self,
+        cash_flow: &CashFlow,
+        owner: Option<&str>,
+    ) -> Result<CashFlow, Error> {
+        self.resolve_cash_flow_with(cash_flow, owner, Person::death_date)
+    }
+
+    pub fn resolve_account(&self, account: &Account) -> Result<Account, Error> {
+        if let Some(owner) = &account.owner {
+            if self.member(owner).is_none() {
+                return Err(Error::Validation(format!(
+                    "Unknown household member: {}",
+                    owner
+                )));
+            }
+        }
+
+        let mut resolved = account.clone();
+        resolved.cash_flows = account
+            .cash_flows
+            .iter()
+            .map(|cf| self.resolve_cash_flow(cf, account.owner.as_deref()))
+            .collect::<Result<Vec<CashFlow>, Error>>()?;
+        Ok(resolved)
+    }
+
+    /// The age of `account`'s owner on `date`, if it has one.
+    pub fn owner_age_on(&self, account: &Account, date: NaiveDate) -> Option<u32> {
+        account
+            .owner
+            .as_ref()
+            .and_then(|o| self.member(o))
+            .map(|p| p.age_on(date))
+    }
+}
+
+fn offset(d: NaiveDate, months: i32) -> Result<NaiveDate, Error> {
+    let months_away = Months::new(months.unsigned_abs());
+    if months >= 0 {
+        d.checked_add_months(months_away)
+    } else {
+        d.checked_sub_months(months_away)
+    }
+    .ok_or(Error::Validation(format!(
+        "A life event is offset {} months from {}, which is out of range",
+        months, d
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::cash::Frequency;
+
+    fn household() -> Household {
+        let mut parent = Person::new(
+            "Alex".to_string(),
+            NaiveDate::from_ymd_opt(1980, 6, 15).unwrap(),
+        );
+        parent.retirement_date = NaiveDate::from_ymd_opt(2045, 7, 1);
+        parent.life_expectancy = Some(90);
+        let child = Person::new(
+            "Sam".to_string(),
+            NaiveDate::from_ymd_opt(2015, 9, 1).unwrap(),
+        );
+        Household::new(vec![parent, child])
+    }
+
+    #[test]
+    fn test_age_on() {
+        let h = household();
+        let alex = h.member("Alex").unwrap();
+        assert_eq!(
+            alex.age_on(NaiveDate::from_ymd_opt(2024, 6, 14).unwrap()),
+            43
+        );
+        assert_eq!(
+            alex.age_on(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()),
+            44
+        );
+        assert_eq!(
+            alex.death_date().unwrap(),
+            NaiveDate::from_ymd_opt(2070, 6, 15)
+        );
+    }
+
+    #[test]
+    fn test_resolve_cash_flows() {
+        let h = household();
+        let mut account = Account::default();
+        account.owner = Some("Alex".to_string());
+
+        let mut salary = CashFlow::new(
+            Some("Salary".to_string()),
+            120_000.0,
+            Some(Frequency::SemiMonthly),
+            None,
+            None,
+            None,
+            None,
+        );
+        salary.end_event = Some(LifeEvent {
+            person: None,
+            kind: LifeEventKind::Retirement,
+            offset_months: -1,
+        });
+        account.add_cash_flow(salary);
+
+        let mut tuition = CashFlow::new(
+            Some("Tuition".to_string()),
+            -30_000.0,
+            Some(Frequency::MonthStart),
+            None,
+            None,
+            None,
+            None,
+        );
+        tuition.start_event = Some(LifeEvent::new(
+            Some("Sam".to_string()),
+            LifeEventKind::Age(18),
+        ));
+        tuition.end_event = Some(LifeEvent::new(
+            Some("Sam".to_string()),
+            LifeEventKind::Age(22),
+        ));
+        account.add_cash_flow(tuition);
+
+        let resolved = h.resolve_account(&account).unwrap();
+        assert_eq!(
+            resolved.cash_flows[0].end_date,
+            NaiveDate::from_ymd_opt(2045, 6, 1)
+        );
+        assert_eq!(
+            resolved.cash_flows[1].start_date,
+            NaiveDate::from_ymd_opt(2033, 9, 1)
+        );
+        assert_eq!(
+            resolved.cash_flows[1].end_date,
+            NaiveDate::from_ymd_opt(2037, 9, 1)
+        );
+        assert!(resolved
+            .cash_flows
+            .iter()
+            .all(|cf| cf.start_event.is_none()));
+    }
+
+    #[test]
+    fn test_unknown_member() {
+        let h = household();
+        let mut account = Account::default();
+        account.owner = Some("Jordan".to_string());
+        assert!(h.resolve_account(&account).is_err());
+    }
+
+    #[test]
+    fn test_events_out_of_range() {
+        let mut h = household();
+        h.members[0].life_expectancy = Some(u32::MAX);
+        assert!(matches!(
+            h.members[0].death_date(),
+            Err(Error::Validation(_))
+        ));
+
+        let mut account = Account::default();
+        let mut allowance = CashFlow::new(
+            Some("Allowance".to_string()),
+            -50.0,
+            Some(Frequency::MonthStart),
+            None,
+            None,
+            None,
+            None,
+        );
+        allowance.start_event = Some(LifeEvent::new(
+            Some("Sam".to_string()),
+            LifeEventKind::Age(400_000_000),
+        ));
+        account.add_cash_flow(allowance.clone());
+        assert!(matches!(
+            h.resolve_account(&account),
+            Err(Error::Validation(_))
+        ));
+
+        allowance.start_event = Some(LifeEvent {
+            person: Some("Sam".to_string()),
+            kind: LifeEventKind::Age(10),
+            offset_months: i32::MIN,
+        });
+        account.cash_flows = vec![allowance];
+        assert!(matches!(
+            h.resolve_account(&account),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_mortality_table() {
+        let person = |table: &str| {
+            serde_json::from_str::<Person>(&format!(
+                r#"{{"name": "Alex", "birth_date": "1980-06-15", "retirement_date": null,
+                "life_expectancy": null, "mortality_table": "{}"}}"#,
+                table
+            ))
+        };
+        assert_eq!(
+            person("Female").unwrap().mortality_table,
+            Some(BundledTable::Female)
+        );
+        assert!(person("Femal").is_err());
+    }
+}
@@ -0,0 +1,438 @@
+use super::cash::{CashFlow, Frequency};
+use super::household::{LifeEvent, LifeEventKind};
+use chrono::{Datelike, Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+const EARLIEST_CLAIMING_MONTHS: u32 = 62 * 12;
+const LATEST_CREDIT_MONTHS: u32 = 70 * 12;
+
+/// Full retirement age, in months, for someone born in `birth_year`.
+pub fn full_retirement_age_months(birth_year: i32) -> u32 {
+    match birth_year {
+        y if y <= 1937 => 65 * 12,
+        1938..=1942 => 65 * 12 + 2 * (birth_year - 1937) as u32,
+        1943..=1954 => 66 * 12,
+        1955..=1959 => 66 * 12 + 2 * (birth_year - 1954) as u32,
+        _ => 67 * 12,
+    }
+}
+
+/// Fraction of the PIA paid when claiming at `claiming_months` of age.
+///
+/// Benefits are reduced by 5/9 of 1% for each of the first 36 months before full retirement
+/// age and 5/12 of 1% for each month beyond that, and increased by 2/3 of 1% for each month of
+/// delay past full retirement age, up to age 70.
+pub fn benefit_factor(claiming_months: u32, full_retirement_months: u32) -> f64 {
+    let claiming_months = claiming_months.clamp(EARLIEST_CLAIMING_MONTHS, LATEST_CREDIT_MONTHS);
+    if claiming_months < full_retirement_months {
+        let early = (full_retirement_months - claiming_months) as f64;
+        1.0 - early.min(36.0) * 5.0 / 900.0 - (early - 36.0).max(0.0) * 5.0 / 1200.0
+    } else {
+        let delayed = (claiming_months - full_retirement_months) as f64;
+        1.0 + delayed * 2.0 / 300.0
+    }
+}
+
+/// Splits a benefit that rises with `cola` every January into one monthly `CashFlow` per
+/// calendar year between `start_date` and `end_date`.
+fn cola_cash_flows(
+    name: &str,
+    annual_amount: f64,
+    cola: f64,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    tax_rate: Option<f64>,
+) -> Vec<CashFlow> {
+    let mut flows = vec![];
+    let mut amount = annual_amount;
+    let mut from = start_date;
+    while from <= end_date {
+        let year_end = NaiveDate::from_ymd_opt(from.year(), 12, 31).unwrap();
+        flows.push(CashFlow::new(
+            Some(name.to_string()),
+            amount,
+            Some(Frequency::MonthStart),
+            Some(from),
+            Some(year_end.min(end_date)),
+            tax_rate,
+            Some(vec!["Income".to_string()]),
+        ));
+        from = year_end.succ_opt().unwrap();
+        amount *= 1.0 + cola;
+    }
+    flows
+}
+
+/// `SocialSecurity` estimates a retirement benefit from a primary insurance amount.
+///
+/// `pia` is the monthly benefit payable at full retirement age, in dollars of the claiming year.
+/// `claiming_age` is in years, and may be fractional (e.g. `62.5`). Ages before 62 are treated as
+/// 62 and ages after 70 as 70. Benefits start on the first of the month after the claiming age is
+/// reached, and rise with `cola` every January after that.
+/// If `beneficiary` names a household member, benefits stop when they die.
+///
+/// # Example
+///
+/// ```
+/// use tortoise::sim::income::SocialSecurity;
+///
+/// let ss = SocialSecurity::new(
+///     "Social Security".to_string(),
+///     2_500.0,
+///     chrono::NaiveDate::from_ymd_opt(1965, 4, 10).unwrap(),
+///     70.0,
+///     0.02,
+/// );
+/// let flows = ss.cash_flows(chrono::NaiveDate::from_ymd_opt(2060, 12, 31).unwrap());
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct SocialSecurity {
+    pub name: String,
+    pub pia: f64,
+    pub birth_date: NaiveDate,
+    pub claiming_age: f64,
+    pub cola: f64,
+    pub end_date: Option<NaiveDate>,
+    pub tax_rate: Option<f64>,
+    #[serde(default)]
+    pub beneficiary: Option<String>,
+}
+
+impl SocialSecurity {
+    pub fn new(
+        name: String,
+        pia: f64,
+        birth_date: NaiveDate,
+        claiming_age: f64,
+        cola: f64,
+    ) -> SocialSecurity {
+        SocialSecurity {
+            name,
+            pia,
+            birth_date,
+            claiming_age,
+            cola,
+            end_date: None,
+            tax_rate: None,
+            beneficiary: None,
+        }
+    }
+
+    fn claiming_months(&self) -> u32 {
+        let months = (self.claiming_age * 12.0).round() as u32;
+        months.clamp(EARLIEST_CLAIMING_MONTHS, LATEST_CREDIT_MONTHS)
+    }
+
+    pub fn claiming_date(&self) -> NaiveDate {
+        let reached = self
+            .birth_date
+            .checked_add_months(Months::new(self.claiming_months()))
+            .unwrap();
+        NaiveDate::from_ymd_opt(reached.year(), reached.month(), 1)
+            .unwrap()
+            .checked_add_months(Months::new(1))
+            .unwrap()
+    }
+
+    /// The monthly benefit in the first year of payment.
+    pub fn monthly_benefit(&self) -> f64 {
+        let fra = full_retirement_age_months(self.birth_date.year());
+        self.pia * benefit_factor(self.claiming_months(), fra)
+    }
+
+    /// Benefit payments from the claiming date through `end_date`, or `through` if earlier.
+    pub fn cash_flows(&self, through: NaiveDate) -> Vec<CashFlow> {
+        let end = self.end_date.map_or(through, |e| e.min(through));
+        let mut flows = cola_cash_flows(
+            &self.name,
+            self.monthly_benefit() * 12.0,
+            self.cola,
+            self.claiming_date(),
+            end,
+            self.tax_rate,
+        );
+        for cf in flows.iter_mut() {
+            cf.end_event = death_of(&self.beneficiary);
+        }
+        flows
+    }
+}
+
+/// `Pension` is a defined-benefit pension paying `annual_benefit` from `start_date`, rising
+/// with `cola` every January.
+///
+/// Payments to the retiree stop at `end_date`. If `survivor_percentage` is non-zero, a survivor
+/// benefit of that fraction of the benefit then being paid continues until `survivor_end_date`.
+///
+/// When `beneficiary` names a household member, payments to them stop when they die instead, and
+/// the survivor benefit runs from then until the death of `survivor`, so that both follow the
+/// death dates sampled for each path.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Pension {
+    pub name: String,
+    pub annual_benefit: f64,
+    pub start_date: NaiveDate,
+    pub cola: f64,
+    pub end_date: Option<NaiveDate>,
+    pub survivor_percentage: f64,
+    pub survivor_end_date: Option<NaiveDate>,
+    pub tax_rate: Option<f64>,
+    #[serde(default)]
+    pub beneficiary: Option<String>,
+    #[serde(default)]
+    pub survivor: Option<String>,
+}
+
+impl Pension {
+    pub fn new(name: String, annual_benefit: f64, start_date: NaiveDate, cola: f64) -> Pension {
+        Pension {
+            name,
+            annual_benefit,
+            start_date,
+            cola,
+            end_date: None,
+            survivor_percentage: 0.0,
+            survivor_end_date: None,
+            tax_rate: None,
+            beneficiary: None,
+            survivor: None,
+        }
+    }
+
+    /// Pension payments, including any survivor benefit, through `through`.
+    pub fn cash_flows(&self, through: NaiveDate) -> Vec<CashFlow> {
+        let end = self.end_date.map_or(through, |e| e.min(through));
+        let mut flows = cola_cash_flows(
+            &self.name,
+            self.annual_benefit,
+            self.cola,
+            self.start_date,
+            end,
+            self.tax_rate,
+        );
+
+        if self.beneficiary.is_some() {
+            for cf in flows.iter_mut() {
+                cf.end_event = death_of(&self.beneficiary);
+            }
+            if self.survivor_percentage > 0.0 {
+                // The survivor benefit could start in any year, so it is scheduled over the whole
+                // horizon and left to the life events to switch on
+                let survivor_end = self.survivor_end_date.map_or(through, |e| e.min(through));
+                let mut survivor = cola_cash_flows(
+                    &format!("{} Survivor", self.name),
+                    self.annual_benefit * self.survivor_percentage,
+                    self.cola,
+                    self.start_date,
+                    survivor_end,
+                    self.tax_rate,
+                );
+                for cf in survivor.iter_mut() {
+                    cf.start_event = death_of(&self.beneficiary);
+                    cf.end_event = death_of(&self.survivor);
+                }
+                flows.append(&mut survivor);
+            }
+            return flows;
+        }
+
+        if let Some(end_date) = self.end_date {
+            if self.survivor_percentage > 0.0 && end_date < through {
+                let survivor_start = end_date.succ_opt().unwrap().max(self.start_date);
+                let years = (survivor_start.year() - self.start_date.year()).max(0);
+                let benefit =
+                    self.annual_benefit * (1.0 + self.cola).powi(years) * self.survivor_percentage;
+                let survivor_end = self.survivor_end_date.map_or(through, |e| e.min(through));
+                flows.append(&mut cola_cash_flows(
+                    &format!("{} Survivor", self.name),
+                    benefit,
+                    self.cola,
+                    survivor_start,
+                    survivor_end,
+                    self.tax_rate,
+                ));
+            }
+        }
+        flows
+    }
+}
+
+fn death_of(person: &Option<String>) -> Option<LifeEvent> {
+    person
+        .as_ref()
+        .map(|p| LifeEvent::new(Some(p.clone()), LifeEventKind::Death))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::cash::Account;
+
+    fn total(flows: &[CashFlow], start: NaiveDate, end: NaiveDate) -> f64 {
+        flows
+            .iter()
+            .flat_map(|cf| cf.payments(start, end, false))
+            .fold(0.0, |acc, p| acc + p.amount)
+    }
+
+    #[test]
+    fn test_full_retirement_age() {
+        assert_eq!(full_retirement_age_months(1950), 66 * 12);
+        assert_eq!(full_retirement_age_months(1957), 66 * 12 + 6);
+        assert_eq!(full_retirement_age_months(1960), 67 * 12);
+    }
+
+    #[test]
+    fn test_benefit_factor() {
+        let fra = 67 * 12;
+        assert!((benefit_factor(62 * 12, fra) - 0.70).abs() < 1e-9);
+        assert!((benefit_factor(64 * 12, fra) - (1.0 - 0.2)).abs() < 1e-9);
+        assert_eq!(benefit_factor(67 * 12, fra), 1.0);
+        assert!((benefit_factor(70 * 12, fra) - 1.24).abs() < 1e-9);
+        assert!((benefit_factor(72 * 12, fra) - 1.24).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_social_security_cash_flows() {
+        let ss = SocialSecurity::new(
+            "Social Security".to_string(),
+            2_000.0,
+            NaiveDate::from_ymd_opt(1960, 3, 15).unwrap(),
+            62.0,
+            0.02,
+        );
+        assert_eq!(
+            ss.claiming_date(),
+            NaiveDate::from_ymd_opt(2022, 4, 1).unwrap()
+        );
+
+        let flows = ss.cash_flows(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert_eq!(flows.len(), 3);
+
+        let first_year = total(
+            &flows,
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 12, 31).unwrap(),
+        );
+        assert!((first_year - 9.0 * 1_400.0).abs() < 1e-6);
+
+        let third_year = total(
+            &flows,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        );
+        assert!((third_year - 12.0 * 1_400.0 * 1.02f64.powi(2)).abs() < 1e-6);
+
+        // Benefits slot into any account
+        let mut account = Account::default();
+        for cf in flows {
+            account.add_cash_flow(cf);
+        }
+        assert_eq!(account.cash_flows.len(), 3);
+
+        // Claiming ages are held to 62 through 70
+        let mut early = ss.clone();
+        early.claiming_age = -5.0;
+        assert_eq!(early.claiming_date(), ss.claiming_date());
+        let mut late = ss.clone();
+        late.claiming_age = 75.0;
+        assert_eq!(
+            late.claiming_date(),
+            NaiveDate::from_ymd_opt(2030, 4, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pension_survivor_benefit() {
+        let mut pension = Pension::new(
+            "Pension".to_string(),
+            24_000.0,
+            NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            0.0,
+        );
+        pension.end_date = NaiveDate::from_ymd_opt(2034, 6, 30);
+        pension.survivor_percentage = 0.5;
+
+        let flows = pension.cash_flows(NaiveDate::from_ymd_opt(2040, 12, 31).unwrap());
+        let survivor: Vec<&CashFlow> = flows
+            .iter()
+            .filter(|cf| cf.name == Some("Pension Survivor".to_string()))
+            .collect();
+        assert_eq!(survivor[0].start_date, NaiveDate::from_ymd_opt(2034, 7, 1));
+        assert_eq!(survivor[0].amount, 12_000.0);
+
+        let year = total(
+            &flows,
+            NaiveDate::from_ymd_opt(2034, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2034, 12, 31).unwrap(),
+        );
+        assert!((year - (6.0 * 2_000.0 + 6.0 * 1_000.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pension_survivor_on_death() {
+        use crate::sim::household::{Household, Person};
+
+        let mut pension = Pension::new(
+            "Pension".to_string(),
+            24_000.0,
+            NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            0.0,
+        );
+        pension.survivor_percentage = 0.5;
+        pension.beneficiary = Some("Alex".to_string());
+        pension.survivor = Some("Jordan".to_string());
+
+        let household = Household::new(vec![
+            Person::new(
+                "Alex".to_string(),
+                NaiveDate::from_ymd_opt(1965, 1, 1).unwrap(),
+            ),
+            Person::new(
+                "Jordan".to_string(),
+                NaiveDate::from_ymd_opt(1967, 1, 1).unwrap(),
+            ),
+        ]);
+        let through = NaiveDate::from_ymd_opt(2040, 12, 31).unwrap();
+        let resolve = |alex: NaiveDate, jordan: NaiveDate| -> Vec<CashFlow> {
+            pension
+                .cash_flows(through)
+                .iter()
+                .map(|cf| {
+                    household
+                        .resolve_cash_flow_with(cf, None, |p| {
+                            Ok(Some(if p.name == "Alex" { alex } else { jordan }))
+                        })
+                        .unwrap()
+                })
+                .collect()
+        };
+        let year = |flows: &[CashFlow], y| {
+            total(
+                flows,
+                NaiveDate::from_ymd_opt(y, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(y, 12, 31).unwrap(),
+            )
+        };
+
+        // Alex dies mid-2034 and Jordan collects half until dying in 2037
+        let flows = resolve(
+            NaiveDate::from_ymd_opt(2034, 6, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2037, 12, 15).unwrap(),
+        );
+        assert!((year(&flows, 2034) - (6.0 * 2_000.0 + 6.0 * 1_000.0)).abs() < 1e-6);
+        assert!((year(&flows, 2036) - 12_000.0).abs() < 1e-6);
+        assert_eq!(year(&flows, 2038), 0.0);
+
+        // Jordan dies first, so there is no survivor benefit
+        let flows = resolve(
+            NaiveDate::from_ymd_opt(2034, 6, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2032, 1, 15).unwrap(),
+        );
+        assert_eq!(year(&flows, 2035), 0.0);
+    }
+}
@@ -0,0 +1,194 @@
+use super::validation::Problem;
+use chrono::{Datelike, NaiveDate};
+use ndarray::Array1;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// How often accrued interest is posted to the account balance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum Compounding {
+    Daily,
+    Monthly,
+    Quarterly,
+    Annually,
+}
+
+impl Compounding {
+    pub fn periods_per_year(&self) -> f64 {
+        match self {
+            Compounding::Daily => 365.0,
+            Compounding::Monthly => 12.0,
+            Compounding::Quarterly => 4.0,
+            Compounding::Annually => 1.0,
+        }
+    }
+
+    /// Interest is posted at the end of each compounding period.
+    pub fn posts_on(&self, d: &NaiveDate) -> bool {
+        let month_end = d.succ_opt().map(|n| n.day() == 1).unwrap_or(true);
+        match self {
+            Compounding::Daily => true,
+            Compounding::Monthly => month_end,
+            Compounding::Quarterly => month_end && matches!(d.month(), 3 | 6 | 9 | 12),
+            Compounding::Annually => month_end && d.month() == 12,
+        }
+    }
+}
+
+/// Day-count convention used to turn an annual rate into a daily accrual.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum DayCount {
+    Act365,
+    Act360,
+    Thirty360,
+}
+
+impl DayCount {
+    pub fn year_fraction(&self, from: &NaiveDate, to: &NaiveDate) -> f64 {
+        match self {
+            DayCount::Act365 => (*to - *from).num_days() as f64 / 365.0,
+            DayCount::Act360 => (*to - *from).num_days() as f64 / 360.0,
+            DayCount::Thirty360 => {
+                // US (bond basis) 30/360
+                let d1 = from.day().min(30) as i64;
+                let d2 = if d1 == 30 && to.day() == 31 {
+                    30
+                } else {
+                    to.day() as i64
+                };
+                let days = 360 * (to.year() - from.year()) as i64
+                    + 30 * (to.month() as i64 - from.month() as i64)
+                    + (d2 - d1);
+                days as f64 / 360.0
+            }
+        }
+    }
+}
+
+/// `InterestRate` describes the interest earned (or charged) on an `Account`'s cash balance.
+///
+/// `rate` is the annual nominal rate; use `InterestRate::from_apy` to convert from an annual
+/// percentage yield. Interest accrues daily and is posted on the `compounding` schedule.
+/// Negative balances accrue at `overdraft_rate` when one is given. Accounts invested in a
+/// portfolio earn the portfolio's return instead, and don't accrue interest.
+///
+/// # Example
+///
+/// ```
+/// use tortoise::sim::interest::{Compounding, DayCount, InterestRate};
+///
+/// let hysa = InterestRate::from_apy(0.045, Compounding::Monthly, DayCount::Act365);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct InterestRate {
+    pub rate: f64,
+    pub compounding: Compounding,
+    pub day_count: DayCount,
+    pub overdraft_rate: Option<f64>,
+}
+
+impl InterestRate {
+    pub fn new(rate: f64, compounding: Compounding, day_count: DayCount) -> InterestRate {
+        InterestRate {
+            rate,
+            compounding,
+            day_count,
+            overdraft_rate: None,
+        }
+    }
+
+    pub fn from_apy(apy: f64, compounding: Compounding, day_count: DayCount) -> InterestRate {
+        let n = compounding.periods_per_year();
+        let rate = n * ((1.0 + apy).powf(1.0 / n) - 1.0);
+        InterestRate::new(rate, compounding, day_count)
+    }
+
+    /// Every problem with the rates that would poison the balances they accrue on.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        for (path, rate) in [
+            ("rate", Some(self.rate)),
+            ("overdraft_rate", self.overdraft_rate),
+        ] {
+            match rate {
+                Some(r) if !r.is_finite() => problems.push(Problem::new(path, "is not a number")),
+                Some(r) if r < 0.0 => problems.push(Problem::new(path, "must be zero or more")),
+                _ => {}
+            }
+        }
+        problems
+    }
+
+    pub fn rate_for(&self, balance: f64) -> f64 {
+        if balance < 0.0 {
+            self.overdraft_rate.unwrap_or(self.rate)
+        } else {
+            self.rate
+        }
+    }
+
+    /// Interest accrued over the single day ending on `d`.
+    pub fn accrue(&self, balance: &Array1<f64>, d: &NaiveDate) -> Array1<f64> {
+        let fraction = self.day_count.year_fraction(&d.pred_opt().unwrap(), d);
+        balance.mapv(|b| b * self.rate_for(b) * fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accrue_year(interest: &InterestRate, balance: f64) -> f64 {
+        let mut b = Array1::<f64>::zeros(1) + balance;
+        let mut accrued = Array1::<f64>::zeros(1);
+        let mut d = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        while d <= NaiveDate::from_ymd_opt(2023, 12, 31).unwrap() {
+            accrued += &interest.accrue(&b, &d);
+            if interest.compounding.posts_on(&d) {
+                b += &accrued;
+                accrued.fill(0.0);
+            }
+            d = d.succ_opt().unwrap();
+        }
+        b[0]
+    }
+
+    #[test]
+    fn test_apy() {
+        let daily = InterestRate::from_apy(0.045, Compounding::Daily, DayCount::Act365);
+        assert!((accrue_year(&daily, 10_000.0) - 10_450.0).abs() < 0.01);
+
+        // Months have different lengths under ACT/365, so monthly posting is only approximate
+        let monthly = InterestRate::from_apy(0.045, Compounding::Monthly, DayCount::Act365);
+        assert!((accrue_year(&monthly, 10_000.0) - 10_450.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_thirty_360() {
+        let dc = DayCount::Thirty360;
+        let d = |m, d| NaiveDate::from_ymd_opt(2023, m, d).unwrap();
+        assert_eq!(dc.year_fraction(&d(1, 30), &d(1, 31)), 0.0);
+        assert_eq!(dc.year_fraction(&d(1, 31), &d(2, 1)), 1.0 / 360.0);
+        assert_eq!(dc.year_fraction(&d(2, 28), &d(3, 1)), 3.0 / 360.0);
+        assert_eq!(dc.year_fraction(&d(1, 1), &d(12, 31)), 1.0);
+
+        // Every month earns exactly a twelfth of the annual rate
+        let interest = InterestRate::new(0.12, Compounding::Annually, DayCount::Thirty360);
+        assert!((accrue_year(&interest, 1_000.0) - 1_120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overdraft_rate() {
+        let mut interest = InterestRate::new(0.01, Compounding::Daily, DayCount::Act360);
+        interest.overdraft_rate = Some(0.36);
+        let d = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        let balance = Array1::from_vec(vec![3_600.0, -3_600.0]);
+        let accrued = interest.accrue(&balance, &d);
+        assert!((accrued[0] - 0.1).abs() < 1e-9);
+        assert!((accrued[1] + 3.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,355 @@
+use super::cash::{CashFlow, Frequency, Payment};
+use crate::error::Error;
+use chrono::{Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A change to the annual interest rate of an adjustable-rate `Loan`, effective from `date`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct RateReset {
+    pub date: NaiveDate,
+    pub rate: f64,
+}
+
+/// `Loan` is a liability that is paid down on a monthly amortization schedule.
+///
+/// `rate` is the annual nominal interest rate at origination. Adjustable-rate loans list their
+/// `rate_resets`; the scheduled payment is recalculated over the remaining term whenever the rate
+/// changes. `extra_payments` are applied to principal on the next scheduled payment date, and
+/// shorten the loan rather than reduce the scheduled payment.
+///
+/// Payments are posted against `funding_account` when the loan is part of a `Scenario`.
+///
+/// # Example
+///
+/// ```
+/// use tortoise::sim::loan::Loan;
+///
+/// let loan = Loan::new(
+///     "Mortgage".to_string(),
+///     400_000.0,
+///     0.065,
+///     360,
+///     chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+///     Some("Checking".to_string()),
+/// );
+/// let table = loan.amortization_table().unwrap();
+/// assert_eq!(table.len(), 360);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Loan {
+    pub name: String,
+    pub principal: f64,
+    pub rate: f64,
+    #[serde(default)]
+    pub rate_resets: Vec<RateReset>,
+    pub term_months: u32,
+    pub start_date: NaiveDate,
+    #[serde(default)]
+    pub extra_payments: Vec<CashFlow>,
+    pub funding_account: Option<String>,
+}
+
+/// A single row of a `Loan`'s amortization table. `balance` is the principal remaining after
+/// the payment on `date`.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct AmortizationRow {
+    pub date: NaiveDate,
+    pub rate: f64,
+    pub payment: f64,
+    pub interest: f64,
+    pub principal: f64,
+    pub extra_principal: f64,
+    pub balance: f64,
+}
+
+impl AmortizationRow {
+    pub fn total_payment(&self) -> f64 {
+        self.payment + self.extra_principal
+    }
+}
+
+/// Level monthly payment that pays off `balance` over `months` at the annual `rate`.
+pub fn monthly_payment(balance: f64, rate: f64, months: u32) -> f64 {
+    if months == 0 {
+        return balance;
+    }
+    let r = rate / 12.0;
+    if r == 0.0 {
+        return balance / months as f64;
+    }
+    balance * r / (1.0 - (1.0 + r).powi(-(months as i32)))
+}
+
+impl Loan {
+    pub fn new(
+        name: String,
+        principal: f64,
+        rate: f64,
+        term_months: u32,
+        start_date: NaiveDate,
+        funding_account: Option<String>,
+    ) -> Loan {
+        Loan {
+            name,
+            principal,
+            rate,
+            rate_resets: vec![],
+            term_months,
+            start_date,
+            extra_payments: vec![],
+            funding_account,
+        }
+    }
+
+    pub fn add_rate_reset(&mut self, date: NaiveDate, rate: f64) {
+        self.rate_resets.push(RateReset { date, rate });
+        self.rate_resets.sort_by_key(|r| r.date);
+    }
+
+    pub fn add_extra_payment(&mut self, cash_flow: CashFlow) {
+        self.extra_payments.push(cash_flow);
+    }
+
+    /// The annual rate in effect on `date`.
+    pub fn rate_at(&self, date: NaiveDate) -> f64 {
+        self.rate_resets
+            .iter()
+            .filter(|r| r.date <= date)
+            .max_by(|a, b| a.date.cmp(&b.date))
+            .map(|r| r.rate)
+            .unwrap_or(self.rate)
+    }
+
+    /// The date of the `n`th monthly payment, counting from 1.
+    pub fn payment_date(&self, n: u32) -> Result<NaiveDate, Error> {
+        self.start_date
+            .checked_add_months(Months::new(n))
+            .ok_or(Error::Validation(format!(
+                "Payment {} of loan {} is out of the range of dates",
+                n, self.name
+            )))
+    }
+
+    /// The extra principal due after `after`, up to and including `through`.
+    pub(crate) fn extra_principal_between(&self, after: NaiveDate, through: NaiveDate) -> f64 {
+        let from = after.succ_opt().unwrap();
+        self.extra_payments
+            .iter()
+            .flat_map(|cf| cf.payments(from, through, false))
+            .fold(0.0, |acc, p| acc + p.amount)
+    }
+
+    /// Builds the full amortization schedule, stopping once the loan is paid off.
+    pub fn amortization_table(&self) -> Result<Vec<AmortizationRow>, Error> {
+        let mut rows: Vec<AmortizationRow> = vec![];
+        let mut balance = self.principal;
+        let mut rate = self.rate_at(self.start_date);
+        let mut payment = monthly_payment(balance, rate, self.term_months);
+        let mut prev_date = self.start_date;
+
+        for n in 1..=self.term_months {
+            if balance <= 0.005 {
+                break;
+            }
+
+            // Rates apply to the period that starts on the previous payment date
+            let period_rate = self.rate_at(prev_date);
+            if period_rate != rate {
+                rate = period_rate;
+                payment = monthly_payment(balance, rate, self.term_months - n + 1);
+            }
+
+            let date = self.payment_date(n)?;
+            let interest = balance * rate / 12.0;
+            let mut principal = payment - interest;
+            if principal > balance || n == self.term_months {
+                principal = balance;
+            }
+            balance -= principal;
+
+            let extra_principal = self
+                .extra_principal_between(prev_date, date)
+                .max(0.0)
+                .min(balance);
+            balance -= extra_principal;
+
+            rows.push(AmortizationRow {
+                date,
+                rate,
+                payment: interest + principal,
+                interest,
+                principal,
+                extra_principal,
+                balance,
+            });
+            prev_date = date;
+        }
+        Ok(rows)
+    }
+
+    pub fn payoff_date(&self) -> Result<Option<NaiveDate>, Error> {
+        Ok(self.amortization_table()?.last().map(|r| r.date))
+    }
+
+    pub fn total_interest(&self) -> Result<f64, Error> {
+        Ok(self
+            .amortization_table()?
+            .iter()
+            .fold(0.0, |acc, r| acc + r.interest))
+    }
+
+    /// Outstanding principal on `date`, or zero before the loan originates.
+    pub fn balance_at(&self, date: NaiveDate) -> Result<f64, Error> {
+        Ok(balance_from_table(
+            &self.amortization_table()?,
+            self.start_date,
+            self.principal,
+            date,
+        ))
+    }
+
+    /// Payments made toward the loan, one per amortization row. Amounts are positive; they are
+    /// negated when posted against the funding account.
+    pub fn payments(&self) -> Result<Vec<Payment>, Error> {
+        Ok(self
+            .amortization_table()?
+            .iter()
+            .map(|r| {
+                Payment::new(
+                    r.date,
+                    r.total_payment(),
+                    self.payment_cash_flow(r.date, r.total_payment()),
+                )
+            })
+            .collect())
+    }
+
+    fn payment_cash_flow(&self, date: NaiveDate, amount: f64) -> CashFlow {
+        CashFlow::new(
+            Some(format!("{} Payment", self.name)),
+            amount,
+            Some(Frequency::Once),
+            Some(date),
+            Some(date),
+            None,
+            Some(vec!["Debt".to_string()]),
+        )
+    }
+}
+
+/// Looks up the outstanding principal on `date` from a precomputed amortization table.
+pub fn balance_from_table(
+    table: &[AmortizationRow],
+    start_date: NaiveDate,
+    principal: f64,
+    date: NaiveDate,
+) -> f64 {
+    if date < start_date {
+        return 0.0;
+    }
+    table
+        .iter()
+        .take_while(|r| r.date <= date)
+        .last()
+        .map(|r| r.balance)
+        .unwrap_or(principal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mortgage() -> Loan {
+        Loan::new(
+            "Mortgage".to_string(),
+            200_000.0,
+            0.06,
+            360,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_fixed_rate_amortization() {
+        let loan = mortgage();
+        let table = loan.amortization_table().unwrap();
+
+        assert_eq!(table.len(), 360);
+        assert!((table[0].payment - 1199.10).abs() < 0.01);
+        assert!((table[0].interest - 1000.0).abs() < 1e-9);
+        assert!(table.last().unwrap().balance.abs() < 1e-6);
+        assert_eq!(
+            loan.payoff_date().unwrap(),
+            NaiveDate::from_ymd_opt(2054, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_extra_payments_shorten_term() {
+        let mut loan = mortgage();
+        loan.add_extra_payment(CashFlow::new(
+            Some("Extra Principal".to_string()),
+            6000.0,
+            Some(Frequency::MonthStart),
+            None,
+            None,
+            None,
+            None,
+        ));
+
+        let table = loan.amortization_table().unwrap();
+        assert!(table.len() < 360);
+        assert!(loan.total_interest().unwrap() < mortgage().total_interest().unwrap());
+        assert!(table.last().unwrap().balance.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rate_reset_recalculates_payment() {
+        let mut loan = mortgage();
+        loan.add_rate_reset(NaiveDate::from_ymd_opt(2029, 1, 1).unwrap(), 0.08);
+
+        let table = loan.amortization_table().unwrap();
+        let before = &table[59];
+        let after = &table[60];
+        assert_eq!(before.rate, 0.06);
+        assert_eq!(after.rate, 0.08);
+        assert!(after.payment > before.payment);
+        assert_eq!(table.len(), 360);
+        assert!(table.last().unwrap().balance.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_payment_date_out_of_range() {
+        let mut loan = mortgage();
+        loan.start_date = NaiveDate::MAX - chrono::Duration::days(40);
+        assert!(loan.payment_date(1).is_ok());
+        assert!(matches!(loan.payment_date(2), Err(Error::Validation(_))));
+        assert!(loan.amortization_table().is_err());
+    }
+
+    #[test]
+    fn test_balance_at() {
+        let loan = mortgage();
+        assert_eq!(
+            loan.balance_at(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap())
+                .unwrap(),
+            0.0
+        );
+        assert_eq!(
+            loan.balance_at(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+                .unwrap(),
+            200_000.0
+        );
+        assert!(
+            loan.balance_at(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap())
+                .unwrap()
+                < 200_000.0
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use super::SimulationResult;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// A summary statistic of a simulation, where higher values are better.
+///
+/// - `SuccessProbability` is the fraction of paths on which `account` never drops below zero.
+/// - `TerminalBalance` is the `percentile` (between 0 and 1) of `account`'s ending balance
+///   across paths, e.g. `0.1` for the balance 90% of paths end above.
+/// - `GoalProbability` is the probability of reaching the `Goal` named `goal`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum Metric {
+    SuccessProbability { account: String },
+    TerminalBalance { account: String, percentile: f64 },
+    GoalProbability { goal: String },
+}
+
+impl Metric {
+    pub fn evaluate(&self, results: &HashMap<String, SimulationResult>) -> Result<f64, Error> {
+        let account_result = |account: &String| {
+            results
+                .get(account)
+                .filter(|r| !r.terminal_balances.is_empty())
+                .ok_or(Error::Validation(format!(
+                    "No simulated balances for account {}",
+                    account
+                )))
+        };
+
+        match self {
+            Metric::SuccessProbability { account } => {
+                let r = account_result(account)?;
+                let solvent = r.minimum_balances.iter().filter(|b| **b >= 0.0).count();
+                Ok(solvent as f64 / r.minimum_balances.len() as f64)
+            }
+            Metric::TerminalBalance {
+                account,
+                percentile,
+            } => {
+                let r = account_result(account)?;
+                Ok(quantile(&r.terminal_balances, *percentile))
+            }
+            Metric::GoalProbability { goal } => results
+                .values()
+                .flat_map(|r| &r.goals)
+                .find(|g| &g.goal.name == goal)
+                .map(|g| g.probability)
+                .ok_or(Error::Validation(format!("Unknown goal: {}", goal))),
+        }
+    }
+}
+
+/// The linearly interpolated `q` quantile of `values`, for `q` between 0 and 1.
+pub fn quantile(values: &[f64], q: f64) -> f64 {
+    quantiles(values, &[q])[0]
+}
+
+/// Several quantiles of `values` at once, sorting them only once.
+pub fn quantiles(values: &[f64], qs: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    qs.iter()
+        .map(|q| {
+            let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+            let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+            sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantile() {
+        let values = [4.0, 1.0, 3.0, 2.0, 5.0];
+        assert_eq!(quantile(&values, 0.0), 1.0);
+        assert_eq!(quantile(&values, 0.5), 3.0);
+        assert_eq!(quantile(&values, 0.625), 3.5);
+        assert_eq!(quantile(&values, 1.0), 5.0);
+
+        // NaNs sort above every number instead of panicking
+        assert_eq!(quantile(&[f64::NAN, 2.0, 1.0], 0.5), 2.0);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut r = SimulationResult::new(None, None, None);
+        r.terminal_balances = vec![100.0, -50.0, 300.0, 200.0];
+        r.minimum_balances = vec![10.0, -50.0, 0.0, -1.0];
+        let results = HashMap::from([("Savings".to_string(), r)]);
+
+        let account = "Savings".to_string();
+        let success = Metric::SuccessProbability {
+            account: account.clone(),
+        };
+        assert_eq!(success.evaluate(&results), Ok(0.5));
+
+        let median = Metric::TerminalBalance {
+            account,
+            percentile: 0.5,
+        };
+        assert_eq!(median.evaluate(&results), Ok(150.0));
+
+        let unknown = Metric::SuccessProbability {
+            account: "Checking".to_string(),
+        };
+        assert!(unknown.evaluate(&results).is_err());
+    }
+}
@@ -1,25 +1,42 @@
+use crate::error::Error;
 use crate::sim::cash::{get_account_balance_at, Frequency};
 use crate::sim::portfolio::Invest;
 use ndarray::Array1;
-use serde::Serialize;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use ts_rs::TS;
 
 #[allow(unused_imports)]
 use self::portfolio::{Asset, Portfolio};
 pub mod cash;
+pub mod compare;
+pub mod debt;
 pub mod examples;
 pub mod excel;
+pub mod goal;
+pub mod historical;
+pub mod household;
+pub mod income;
+pub mod interest;
+pub mod loan;
+pub mod metrics;
+pub mod mortality;
 pub mod portfolio;
+pub mod sensitivity;
+pub mod solver;
+pub mod validation;
+pub mod withdrawal;
 
-#[derive(Debug, Serialize, Clone, TS)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[ts(export, export_to = "../src/rustTypes/")]
 pub struct InvestedAccount {
     pub account: cash::Account,
     pub portfolio: Option<portfolio::Portfolio>,
 }
 
-#[derive(Debug, Serialize, Clone, TS)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[ts(export, export_to = "../src/rustTypes/")]
 pub struct Transfer {
     pub from: String,
@@ -30,14 +47,35 @@ pub struct Transfer {
     pub amount: f64,
 }
 
-#[derive(Debug, Serialize, Clone, TS)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[ts(export, export_to = "../src/rustTypes/")]
 pub struct Scenario {
     pub accounts: Vec<InvestedAccount>,
+    #[serde(default)]
     pub transfers: Vec<Transfer>,
+    #[serde(default)]
+    pub loans: Vec<loan::Loan>,
+    #[serde(default)]
+    pub withdrawals: Vec<withdrawal::WithdrawalPolicy>,
+    #[serde(default)]
+    pub goals: Vec<goal::Goal>,
+    #[serde(default)]
+    pub household: Option<household::Household>,
+    #[serde(default)]
+    pub longevity: Option<mortality::Longevity>,
+    #[serde(default)]
+    pub market: historical::MarketModel,
     pub start_date: chrono::NaiveDate,
     pub end_date: chrono::NaiveDate,
     pub num_samples: usize,
+    #[serde(default)]
+    pub inflation: f64,
+    /// Fixes the random draws, so that the same scenario always gives the same result. Each
+    /// account draws its returns from its own stream, derived from the seed and the account's
+    /// name, so adding or removing an account doesn't change the paths of the others.
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub seed: Option<u64>,
 }
 
 impl Scenario {
@@ -60,9 +98,177 @@ impl Scenario {
             start_date: start_date,
             end_date: end_date,
             transfers: vec![],
+            loans: vec![],
+            withdrawals: vec![],
+            goals: vec![],
+            household: None,
+            longevity: None,
+            market: historical::MarketModel::Random,
             num_samples: num_samples,
+            inflation: 0.0,
+            seed: None,
         }
     }
+
+    /// Every problem with the scenario, its accounts and their portfolios that would stop it
+    /// from being simulated.
+    pub fn validate(&self) -> Vec<validation::Problem> {
+        let mut problems = vec![];
+        if self.end_date < self.start_date {
+            problems.push(validation::Problem::new(
+                "end_date",
+                "is before the start date",
+            ));
+        }
+        if self.num_samples == 0 {
+            problems.push(validation::Problem::new(
+                "num_samples",
+                "must be at least 1",
+            ));
+        }
+        if !self.inflation.is_finite() {
+            problems.push(validation::Problem::new("inflation", "is not a number"));
+        }
+        for (i, a) in self.accounts.iter().enumerate() {
+            let path = format!("accounts[{}]", i);
+            problems.extend(validation::nested(
+                &format!("{}.account", path),
+                a.account.validate(),
+            ));
+            if let Some(portfolio) = &a.portfolio {
+                problems.extend(validation::nested(
+                    &format!("{}.portfolio", path),
+                    portfolio.validate(),
+                ));
+            }
+            // Results and references between accounts go by name
+            if let Some(first) = self.accounts[..i]
+                .iter()
+                .position(|other| other.account.name == a.account.name)
+            {
+                problems.push(validation::Problem::new(
+                    &format!("{}.account.name", path),
+                    format!("is also the name of accounts[{}]", first),
+                ));
+            }
+            if self.household.is_none() {
+                for (j, cf) in a.account.cash_flows.iter().enumerate() {
+                    for (field, event) in [
+                        ("start_event", &cf.start_event),
+                        ("end_event", &cf.end_event),
+                    ] {
+                        if event.is_some() {
+                            problems.push(validation::Problem::new(
+                                &format!("{}.account.cash_flows[{}].{}", path, j, field),
+                                "is a life event, but the scenario has no household",
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        let unknown_account =
+            |name: &String| !self.accounts.iter().any(|a| &a.account.name == name);
+        for (i, l) in self.loans.iter().enumerate() {
+            if let Some(funding_account) = &l.funding_account {
+                if unknown_account(funding_account) {
+                    problems.push(validation::Problem::new(
+                        &format!("loans[{}].funding_account", i),
+                        format!("names no account in the scenario: {}", funding_account),
+                    ));
+                }
+            }
+        }
+        for (i, w) in self.withdrawals.iter().enumerate() {
+            if unknown_account(&w.account) {
+                problems.push(validation::Problem::new(
+                    &format!("withdrawals[{}].account", i),
+                    format!("names no account in the scenario: {}", w.account),
+                ));
+            }
+        }
+        for (i, g) in self.goals.iter().enumerate() {
+            let path = format!("goals[{}]", i);
+            if g.date < self.start_date || g.date > self.end_date {
+                problems.push(validation::Problem::new(
+                    &format!("{}.date", path),
+                    "is outside the scenario dates",
+                ));
+            }
+            for (j, account) in g.accounts.iter().enumerate() {
+                if unknown_account(account) {
+                    problems.push(validation::Problem::new(
+                        &format!("{}.accounts[{}]", path, j),
+                        format!("names no account in the scenario: {}", account),
+                    ));
+                }
+            }
+            problems.extend(validation::nested(&path, g.validate()));
+        }
+        problems
+    }
+
+    /// Replaces life events on every account's cash flows with the dates they resolve to for
+    /// the scenario's household.
+    pub fn resolve_life_events(self) -> Result<Scenario, Error> {
+        let household = match &self.household {
+            Some(h) => h.clone(),
+            None => return Ok(self),
+        };
+
+        let mut scenario = self;
+        for a in scenario.accounts.iter_mut() {
+            a.account = household.resolve_account(&a.account)?;
+        }
+        Ok(scenario)
+    }
+
+    /// Samples a death date for each household member on every path when the scenario has
+    /// stochastic longevity, and moves the cash flows that depend on them out of the accounts.
+    /// Returns those flows, resolved separately on each path, by account name.
+    pub fn sample_longevity<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<HashMap<String, mortality::PathFlows>, Error> {
+        let mut path_flows = HashMap::new();
+        let (household, longevity) = match (&self.household, &self.longevity) {
+            (Some(h), Some(l)) => (h, l),
+            _ => return Ok(path_flows),
+        };
+
+        let lifetimes = mortality::Lifetimes::sample(
+            household,
+            longevity,
+            self.start_date,
+            self.num_samples,
+            rng,
+        )?;
+        for a in self.accounts.iter_mut() {
+            let owner = a.account.owner.clone();
+            let (stochastic, fixed): (Vec<cash::CashFlow>, Vec<cash::CashFlow>) = a
+                .account
+                .cash_flows
+                .drain(..)
+                .partition(|cf| mortality::depends_on_death(cf, owner.as_deref()));
+            a.account.cash_flows = fixed;
+            if stochastic.is_empty() {
+                continue;
+            }
+            path_flows.insert(
+                a.account.name.clone(),
+                mortality::PathFlows::new(
+                    stochastic,
+                    owner.as_deref(),
+                    household,
+                    &lifetimes,
+                    a.account.start_date,
+                    self.end_date,
+                    self.num_samples,
+                )?,
+            );
+        }
+        Ok(path_flows)
+    }
 }
 
 #[derive(Serialize, Clone, Debug, TS)]
@@ -83,12 +289,40 @@ impl AccountBalance {
     }
 }
 
+/// The 10th, 50th and 90th percentiles of an account's balance across paths on `date`, one of
+/// the scenario's reporting dates: its first and last days and the last day of every month.
+#[derive(Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct BalancePercentiles {
+    pub date: chrono::NaiveDate,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+impl BalancePercentiles {
+    fn new(date: chrono::NaiveDate, balances: &[f64]) -> BalancePercentiles {
+        let q = metrics::quantiles(balances, &[0.1, 0.5, 0.9]);
+        BalancePercentiles {
+            date,
+            p10: q[0],
+            p50: q[1],
+            p90: q[2],
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug, TS)]
 #[ts(export, export_to = "../src/rustTypes/")]
 pub struct SimulationResult {
     pub balances: Vec<AccountBalance>,
+    pub percentiles: Vec<BalancePercentiles>,
     pub uninvested_balances: Vec<AccountBalance>,
     pub payments: Vec<cash::Payment>,
+    pub withdrawals: Vec<withdrawal::WithdrawalResult>,
+    pub goals: Vec<goal::GoalResult>,
+    pub terminal_balances: Vec<f64>,
+    pub minimum_balances: Vec<f64>,
 }
 
 impl SimulationResult {
@@ -102,13 +336,56 @@ impl SimulationResult {
         let p = payments.unwrap_or(vec![]);
         SimulationResult {
             balances: b,
+            percentiles: vec![],
             uninvested_balances: u,
             payments: p,
+            withdrawals: vec![],
+            goals: vec![],
+            terminal_balances: vec![],
+            minimum_balances: vec![],
         }
     }
 }
 
-pub fn run_simulation(scenario: Scenario) -> Result<HashMap<String, SimulationResult>, String> {
+pub fn run_simulation(scenario: Scenario) -> Result<HashMap<String, SimulationResult>, Error> {
+    let mut results = simulate(scenario.clone())?;
+    goal::size_contributions(&scenario, &mut results)?;
+    Ok(results)
+}
+
+// Percentiles sort every path's balance, so they are only kept on these dates
+fn is_reporting_date(scenario: &Scenario, d: chrono::NaiveDate) -> bool {
+    d == scenario.start_date
+        || d == scenario.end_date
+        || d.succ_opt().is_some_and(|n| chrono::Datelike::day(&n) == 1)
+}
+
+/// The random stream for `account`'s returns. The name is hashed with FNV-1a, which unlike the
+/// standard library's hasher gives the same value in every Rust release.
+fn account_rng(seed: u64, account: &str) -> StdRng {
+    let hash = account.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    StdRng::seed_from_u64(seed ^ hash)
+}
+
+/// `run_simulation` without solving for goal contributions, which simulates the scenario again.
+pub(crate) fn simulate(scenario: Scenario) -> Result<HashMap<String, SimulationResult>, Error> {
+    validation::check(scenario.validate())?;
+    let seed = scenario.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut scenario = scenario;
+    let path_flows = scenario.sample_longevity(&mut rng)?;
+    let scenario = scenario.resolve_life_events()?;
+    if let historical::MarketModel::Historical(replay) = &scenario.market {
+        replay.validate(
+            scenario.end_date,
+            scenario
+                .accounts
+                .iter()
+                .filter_map(|a| a.portfolio.as_ref()),
+        )?;
+    }
     let simulation_frequency = Frequency::BusinesDay;
     let mut results = HashMap::new();
     let mut prev: HashMap<String, Array1<f64>> = HashMap::new();
@@ -119,27 +396,81 @@ pub fn run_simulation(scenario: Scenario) -> Result<HashMap<String, SimulationRe
         );
     }
 
+    // Loans are tracked as negative balances, and their payments are posted against the
+    // funding account
+    let mut linked_payments: HashMap<String, Vec<cash::Payment>> = HashMap::new();
+    let mut posted: HashMap<String, f64> = HashMap::new();
+    let mut path_posted: HashMap<String, Array1<f64>> = HashMap::new();
+    let mut accrued: HashMap<String, Array1<f64>> = HashMap::new();
+    let mut interest_posted: HashMap<String, Array1<f64>> = HashMap::new();
+    let mut minimums: HashMap<String, Array1<f64>> = HashMap::new();
+    let mut withdrawals: Vec<withdrawal::WithdrawalState> = scenario
+        .withdrawals
+        .iter()
+        .map(|w| withdrawal::WithdrawalState::new(w.clone(), scenario.num_samples))
+        .collect();
+    // Each goal sums the balances of its accounts on every path at the goal date
+    let mut goal_balances: Vec<Array1<f64>> =
+        vec![Array1::zeros(scenario.num_samples); scenario.goals.len()];
+    let mut loans = vec![];
+    for l in &scenario.loans {
+        results.insert(l.name.clone(), SimulationResult::new(None, None, None));
+        let payments = l.payments()?;
+        if let Some(funding_account) = &l.funding_account {
+            let postings = linked_payments.entry(funding_account.clone()).or_default();
+            for p in &payments {
+                let mut posting = p.clone();
+                posting.amount = -p.amount;
+                postings.push(posting);
+            }
+        }
+        loans.push((l, l.amortization_table()?, payments));
+    }
+
+    let mut account_rngs: HashMap<String, StdRng> = scenario
+        .accounts
+        .iter()
+        .map(|a| (a.account.name.clone(), account_rng(seed, &a.account.name)))
+        .collect();
+
     let mut d = scenario.start_date;
     while d <= scenario.end_date {
+        let reporting = is_reporting_date(&scenario, d);
         for invested_account in scenario.accounts.iter() {
             let account = &invested_account.account;
             let portfolio = &invested_account.portfolio;
             let num_samples = &scenario.num_samples;
             let account_results = results.get_mut(&account.name).unwrap();
-            
+
             #[allow(unused_assignments)]
             let mut bd: Array1<f64> = Array1::zeros(*num_samples);
 
             let p: Option<&Array1<f64>> = prev.get::<String>(&account.name);
 
-            let uninvested_balance = get_account_balance_at(account.clone(), d, *num_samples);
-            account_results
-                .uninvested_balances
-                .push(AccountBalance::new(
-                    d,
-                    account.name.clone(),
-                    uninvested_balance.mean().unwrap(),
-                ));
+            let linked_flows: Vec<cash::Payment> = linked_payments
+                .get(&account.name)
+                .map(|ps| ps.iter().filter(|p| p.date == d).cloned().collect())
+                .unwrap_or_default();
+            let linked_total = linked_flows.iter().fold(0.0, |acc, x| acc + x.amount);
+            let posted_total = posted.entry(account.name.clone()).or_insert(0.0);
+            *posted_total += linked_total;
+
+            // Flows that depend on when someone dies differ on each path
+            let (path_total, path_payments) = match path_flows.get(&account.name) {
+                Some(f) => f.at(d, *num_samples),
+                None => (Array1::zeros(*num_samples), vec![]),
+            };
+            let path_posted_total = path_posted.entry(account.name.clone()).or_insert_with(|| {
+                match path_flows.get(&account.name) {
+                    Some(f) => f.total_through(d.pred_opt().unwrap(), *num_samples),
+                    None => Array1::zeros(*num_samples),
+                }
+            });
+            *path_posted_total += &path_total;
+
+            let uninvested_balance = get_account_balance_at(account.clone(), d, *num_samples)
+                + *posted_total
+                + &*path_posted_total;
 
             if p.is_some() {
                 bd = p.unwrap().clone();
@@ -147,22 +478,97 @@ pub fn run_simulation(scenario: Scenario) -> Result<HashMap<String, SimulationRe
                     .flows_at(d)
                     .iter()
                     .fold(0.0, |acc, x| acc + x.amount);
+                bd += linked_total;
+                bd += &path_total;
             } else {
                 // Get account balance due to defined cash flows
                 bd = uninvested_balance.clone();
             }
 
-            // Invest the account if a portfolio is defined
-            if portfolio.is_some() {
-                let bd_post_investment = account.invest(
-                    &bd,
-                    portfolio.as_ref().unwrap(),
-                    num_samples,
-                    &simulation_frequency,
-                );
-                bd = bd_post_investment;
+            // Withdrawals are set separately on each path, before the day's investment return
+            let mut withdrawal_payments: Vec<cash::Payment> = vec![];
+            for w in withdrawals
+                .iter_mut()
+                .filter(|w| w.policy.account == account.name)
+            {
+                if let Some(amounts) = w.withdraw(&d, &bd, scenario.inflation) {
+                    bd -= &amounts;
+                    let mean = -amounts.mean().unwrap();
+                    withdrawal_payments.push(cash::Payment::new(
+                        d,
+                        mean,
+                        w.policy.cash_flow(d, mean),
+                    ));
+                }
+            }
+
+            // Invest the account if a portfolio is defined, replaying history if asked to
+            if let Some(portfolio) = portfolio {
+                let replayed = match &scenario.market {
+                    historical::MarketModel::Historical(replay) => {
+                        replay.daily_return(portfolio, &d)
+                    }
+                    historical::MarketModel::Random => None,
+                };
+                bd = match replayed {
+                    Some(ret) => bd * (1.0 + ret),
+                    None => account.invest_using(
+                        &bd,
+                        portfolio,
+                        num_samples,
+                        &simulation_frequency,
+                        account_rngs.get_mut(&account.name).unwrap(),
+                    ),
+                };
+            }
+
+            // Accrue interest daily, and post it on the compounding schedule. Invested accounts
+            // earn their portfolio's return instead.
+            let mut interest_payment: Option<cash::Payment> = None;
+            let interest_total = interest_posted
+                .entry(account.name.clone())
+                .or_insert(Array1::zeros(*num_samples));
+            if let (Some(interest), None) = (&account.interest, portfolio) {
+                let a = accrued
+                    .entry(account.name.clone())
+                    .or_insert(Array1::zeros(*num_samples));
+                *a += &interest.accrue(&bd, &d);
+                if interest.compounding.posts_on(&d) {
+                    bd += &*a;
+                    *interest_total += &*a;
+                    interest_payment = Some(cash::Payment::new(
+                        d,
+                        a.mean().unwrap(),
+                        cash::CashFlow::new(
+                            Some("Interest".to_string()),
+                            a.mean().unwrap(),
+                            Some(Frequency::Once),
+                            Some(d),
+                            Some(d),
+                            None,
+                            Some(vec!["Interest".to_string()]),
+                        ),
+                    ));
+                    a.fill(0.0);
+                }
             }
+            account_results
+                .uninvested_balances
+                .push(AccountBalance::new(
+                    d,
+                    account.name.clone(),
+                    (uninvested_balance + &*interest_total).mean().unwrap(),
+                ));
             prev.insert(account.name.clone(), bd.clone());
+            minimums
+                .entry(account.name.clone())
+                .and_modify(|m| m.zip_mut_with(&bd, |m, b| *m = m.min(*b)))
+                .or_insert(bd.clone());
+            for (g, b) in scenario.goals.iter().zip(goal_balances.iter_mut()) {
+                if g.date == d && g.accounts.contains(&account.name) {
+                    *b += &bd;
+                }
+            }
 
             // Update the results
             account_results.balances.push(AccountBalance::new(
@@ -170,15 +576,59 @@ pub fn run_simulation(scenario: Scenario) -> Result<HashMap<String, SimulationRe
                 account.name.clone(),
                 bd.mean().unwrap(),
             ));
+            if reporting {
+                account_results
+                    .percentiles
+                    .push(BalancePercentiles::new(d, bd.as_slice().unwrap()));
+            }
 
             // Get the cash flows for the day
             let flows = account.flows_at(d);
-            for f in &flows {
+            for f in flows
+                .iter()
+                .chain(&linked_flows)
+                .chain(&path_payments)
+                .chain(&withdrawal_payments)
+                .chain(&interest_payment)
+            {
                 account_results.payments.push(f.clone());
             }
         }
+
+        for (l, table, payments) in &loans {
+            let loan_results = results.get_mut(&l.name).unwrap();
+            let balance = -loan::balance_from_table(table, l.start_date, l.principal, d);
+            let loan_balance = AccountBalance::new(d, l.name.clone(), balance);
+            loan_results.uninvested_balances.push(loan_balance.clone());
+            loan_results.balances.push(loan_balance);
+            if reporting {
+                loan_results
+                    .percentiles
+                    .push(BalancePercentiles::new(d, &[balance]));
+            }
+            for p in payments.iter().filter(|p| p.date == d) {
+                loan_results.payments.push(p.clone());
+            }
+        }
         d = d.succ_opt().unwrap();
     }
+
+    for w in withdrawals {
+        if let Some(account_results) = results.get_mut(&w.policy.account) {
+            account_results.withdrawals.push(w.into_result());
+        }
+    }
+    for (name, bd) in prev {
+        let account_results = results.get_mut(&name).unwrap();
+        account_results.terminal_balances = bd.to_vec();
+        account_results.minimum_balances = minimums[&name].to_vec();
+    }
+    for (g, b) in scenario.goals.iter().zip(&goal_balances) {
+        let goal_result = goal::GoalResult::new(g, b);
+        for a in &g.accounts {
+            results.get_mut(a).unwrap().goals.push(goal_result.clone());
+        }
+    }
     Ok(results)
 }
 
@@ -205,12 +655,102 @@ mod tests {
             start_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
             end_date: chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
             transfers: vec![],
+            loans: vec![],
+            withdrawals: vec![],
+            goals: vec![],
+            household: None,
+            longevity: None,
+            market: historical::MarketModel::Random,
             num_samples: 1,
+            inflation: 0.0,
+            seed: None,
         };
 
         let _r = run_simulation(scenario).unwrap();
     }
 
+    #[test]
+    fn test_validate_scenario() {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        assert!(scenario.validate().is_empty());
+
+        scenario.accounts[1].account.end_date =
+            chrono::NaiveDate::from_ymd_opt(1999, 1, 1).unwrap();
+        scenario.accounts[0].portfolio = Some(Portfolio::new(
+            vec![Asset::new("Equities".to_string(), 0.07, 0.15)],
+            vec![0.5, 0.5],
+        ));
+        scenario.accounts[0].account.cash_flows[0].amount = f64::INFINITY;
+        let paths: Vec<String> = scenario.validate().into_iter().map(|p| p.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "accounts[0].account.cash_flows[0].amount",
+                "accounts[0].portfolio.weights",
+                "accounts[1].account.end_date",
+            ]
+        );
+
+        // Bad input is rejected before it can panic in the engine
+        let r = run_simulation(scenario);
+        assert!(matches!(r, Err(Error::Validation(_))));
+
+        // Account names must be unique, and life events need a household to resolve against
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts[1].account.name = scenario.accounts[0].account.name.clone();
+        scenario.accounts[0].account.cash_flows[0].start_event = Some(household::LifeEvent::new(
+            Some("Alex".to_string()),
+            household::LifeEventKind::Retirement,
+        ));
+        let paths: Vec<String> = scenario.validate().into_iter().map(|p| p.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "accounts[0].account.cash_flows[0].start_event",
+                "accounts[1].account.name",
+            ]
+        );
+        assert!(matches!(
+            run_simulation(scenario),
+            Err(Error::Validation(_))
+        ));
+
+        // Goals and withdrawals must refer to accounts in the scenario, and every problem with
+        // them is reported at once
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        let mut goal = goal::Goal::new(
+            "College".to_string(),
+            f64::NAN,
+            chrono::NaiveDate::from_ymd_opt(2099, 9, 1).unwrap(),
+            vec!["Example".to_string(), "Savings".to_string()],
+        );
+        goal.success_rate = 1.5;
+        let mut no_accounts =
+            goal::Goal::new("Car".to_string(), 20_000.0, scenario.end_date, vec![]);
+        no_accounts.success_rate = 0.5;
+        scenario.goals = vec![goal, no_accounts];
+        scenario.withdrawals = vec![withdrawal::WithdrawalPolicy::new(
+            "Spending".to_string(),
+            "Brokerage".to_string(),
+            withdrawal::WithdrawalStrategy::FixedPercentage { rate: 0.04 },
+            Frequency::MonthStart,
+            scenario.start_date,
+            None,
+        )];
+        let paths: Vec<String> = scenario.validate().into_iter().map(|p| p.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "withdrawals[0].account",
+                "goals[0].date",
+                "goals[0].accounts[1]",
+                "goals[0].target",
+                "goals[0].success_rate",
+                "goals[1].accounts",
+            ]
+        );
+    }
+
     #[test]
     fn test_run_two_account_scenario() {
         let scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
@@ -237,4 +777,262 @@ mod tests {
 
         // TODO: Check that the transfer happened
     }
+
+    #[test]
+    fn test_run_simulation_with_interest() {
+        let mut account = cash::Account::new(
+            "Savings".to_string(),
+            10_000.0,
+            vec![],
+            chrono::NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        );
+        account.set_interest(Some(interest::InterestRate::from_apy(
+            0.045,
+            interest::Compounding::Daily,
+            interest::DayCount::Act365,
+        )));
+        let start = account.start_date;
+        let end = account.end_date;
+        let mut scenario = Scenario::from_accounts(vec![account], start, end, 1);
+        scenario.accounts[0].portfolio = None;
+
+        let r = run_simulation(scenario.clone()).unwrap();
+        let savings = &r["Savings"];
+        assert!((savings.balances.last().unwrap().balance - 10_450.0).abs() < 1.0);
+        assert_eq!(savings.payments.len(), 365);
+        // Posted interest is part of the uninvested balance
+        let uninvested = savings.uninvested_balances.last().unwrap().balance;
+        assert!((uninvested - savings.balances.last().unwrap().balance).abs() < 1e-6);
+
+        // An invested account earns its portfolio's return, not interest as well
+        scenario.seed = Some(7);
+        scenario.accounts[0].portfolio = Portfolio::default();
+        let with_interest = run_simulation(scenario.clone()).unwrap();
+        scenario.accounts[0].account.set_interest(None);
+        let without_interest = run_simulation(scenario).unwrap();
+        assert_eq!(
+            with_interest["Savings"].terminal_balances,
+            without_interest["Savings"].terminal_balances
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_with_withdrawals() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = chrono::NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        let account = cash::Account::new("Brokerage".to_string(), 1_000_000.0, vec![], start, end);
+        let mut scenario = Scenario::from_accounts(vec![account], start, end, 20);
+        scenario.inflation = 0.03;
+        scenario.withdrawals = vec![withdrawal::WithdrawalPolicy::new(
+            "Spending".to_string(),
+            "Brokerage".to_string(),
+            withdrawal::WithdrawalStrategy::FixedPercentage { rate: 0.04 },
+            Frequency::MonthStart,
+            start,
+            None,
+        )];
+
+        let r = run_simulation(scenario).unwrap();
+        let w = &r["Brokerage"].withdrawals[0];
+        assert_eq!(w.paths.len(), 20);
+        assert_eq!(w.paths[0].len(), 36);
+        assert!((w.paths[0][0].amount + 40_000.0 / 12.0).abs() < 1e-6);
+        assert!(w.stats.std_dev > 0.0);
+    }
+
+    #[test]
+    fn test_run_simulation_with_household() {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        let mut person = household::Person::new(
+            "Alex".to_string(),
+            chrono::NaiveDate::from_ymd_opt(1979, 7, 1).unwrap(),
+        );
+        person.retirement_date = chrono::NaiveDate::from_ymd_opt(2024, 7, 1);
+        scenario.household = Some(household::Household::new(vec![person]));
+
+        let account = &mut scenario.accounts[0].account;
+        account.owner = Some("Alex".to_string());
+        account.cash_flows[0].end_event = Some(household::LifeEvent::new(
+            None,
+            household::LifeEventKind::Retirement,
+        ));
+
+        let r = run_simulation(scenario).unwrap();
+        let income_after_retirement = r["Example"]
+            .payments
+            .iter()
+            .filter(|p| p.cash_flow.name == Some("Income".to_string()))
+            .any(|p| p.date > chrono::NaiveDate::from_ymd_opt(2024, 7, 1).unwrap());
+        assert!(!income_after_retirement);
+    }
+
+    #[test]
+    fn test_run_simulation_with_longevity() {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario.num_samples = 500;
+        scenario.seed = Some(7);
+        let mut person = household::Person::new(
+            "Alex".to_string(),
+            chrono::NaiveDate::from_ymd_opt(1929, 3, 1).unwrap(),
+        );
+        person.mortality_table = Some(mortality::BundledTable::Male);
+        scenario.household = Some(household::Household::new(vec![person]));
+        scenario.accounts[0].account.owner = Some("Alex".to_string());
+        let fixed = run_simulation(scenario.clone()).unwrap();
+
+        scenario.longevity = Some(mortality::Longevity { improvement: 0.0 });
+        let r = run_simulation(scenario).unwrap();
+        let income: Vec<&cash::Payment> = r["Example"]
+            .payments
+            .iter()
+            .filter(|p| p.cash_flow.name == Some("Income".to_string()) && p.amount > 0.0)
+            .collect();
+        assert_eq!(income.len(), 24);
+
+        // Income stops on the paths where the owner has died
+        assert!(income[23].amount < income[0].amount);
+        assert!(
+            r["Example"].balances.last().unwrap().balance
+                < fixed["Example"].balances.last().unwrap().balance
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_with_goals() {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario.num_samples = 10;
+
+        // The account's flows net to -11,000 over the year, leaving 19,000 at the end
+        scenario.accounts[0].account.balance = 30_000.0;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let account = vec!["Example".to_string()];
+        scenario.goals = vec![
+            goal::Goal::new(
+                "Emergency Fund".to_string(),
+                15_000.0,
+                date,
+                account.clone(),
+            ),
+            goal::Goal::new("House".to_string(), 25_000.0, date, account),
+        ];
+
+        let r = run_simulation(scenario.clone()).unwrap();
+        let goals = &r["Example"].goals;
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[0].probability, 1.0);
+        assert_eq!(goals[0].required_monthly_contribution, Some(0.0));
+        assert_eq!(goals[1].probability, 0.0);
+        assert!((goals[1].median_shortfall - 6_000.0).abs() < 1e-6);
+        // Twelve monthly contributions make up the 6,000 shortfall
+        let contribution = goals[1].required_monthly_contribution.unwrap();
+        assert!((contribution - 6_000.0 / 12.0).abs() < 2.0);
+
+        // Contributions to an account earning interest grow, so less is needed
+        scenario.accounts[0]
+            .account
+            .set_interest(Some(interest::InterestRate::from_apy(
+                0.05,
+                interest::Compounding::Daily,
+                interest::DayCount::Act365,
+            )));
+        let r = run_simulation(scenario).unwrap();
+        let with_interest = r["Example"].goals[1].required_monthly_contribution.unwrap();
+        assert!(with_interest < contribution - 1.0);
+
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.goals = vec![goal::Goal::new(
+            "College".to_string(),
+            200_000.0,
+            chrono::NaiveDate::from_ymd_opt(2035, 9, 1).unwrap(),
+            vec!["Example".to_string()],
+        )];
+        assert!(run_simulation(scenario).is_err());
+    }
+
+    #[test]
+    fn test_run_simulation_with_seed() {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario.accounts[0].portfolio = Portfolio::default();
+        scenario.num_samples = 20;
+        scenario.seed = Some(42);
+
+        let a = run_simulation(scenario.clone()).unwrap();
+        let b = run_simulation(scenario.clone()).unwrap();
+        assert_eq!(a["Example"].terminal_balances.len(), 20);
+        assert_eq!(
+            a["Example"].terminal_balances,
+            b["Example"].terminal_balances
+        );
+
+        // Another account draws its own returns, leaving this one's paths unchanged
+        let mut other = scenario.accounts[0].clone();
+        other.account.name = "Other".to_string();
+        let mut with_other = scenario.clone();
+        with_other.accounts.insert(0, other);
+        let d = run_simulation(with_other).unwrap();
+        assert_eq!(
+            a["Example"].terminal_balances,
+            d["Example"].terminal_balances
+        );
+        assert_ne!(d["Example"].terminal_balances, d["Other"].terminal_balances);
+
+        scenario.seed = Some(43);
+        let c = run_simulation(scenario).unwrap();
+        assert_ne!(
+            a["Example"].terminal_balances,
+            c["Example"].terminal_balances
+        );
+        assert!(a["Example"]
+            .minimum_balances
+            .iter()
+            .zip(&a["Example"].terminal_balances)
+            .all(|(m, t)| m <= t));
+    }
+
+    #[test]
+    fn test_run_simulation_with_loan() {
+        let scenario = crate::sim::examples::mortgage_scenario::mortgage_scenario();
+        let mortgage = scenario.loans[0].clone();
+
+        // A loan funded from an account that isn't in the scenario is rejected
+        let mut unfunded = scenario.clone();
+        unfunded.loans[0].funding_account = Some("Savings".to_string());
+        let paths: Vec<String> = unfunded.validate().into_iter().map(|p| p.path).collect();
+        assert_eq!(paths, vec!["loans[0].funding_account"]);
+        assert!(matches!(
+            run_simulation(unfunded),
+            Err(Error::Validation(_))
+        ));
+
+        let r = run_simulation(scenario).unwrap();
+
+        let loan_results = &r["Mortgage"];
+        let first_payment = &loan_results.payments[0];
+        assert_eq!(
+            first_payment.date,
+            chrono::NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()
+        );
+        assert_eq!(
+            loan_results.balances.last().unwrap().balance,
+            -mortgage
+                .balance_at(chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+                .unwrap()
+        );
+
+        // The funding account pays the same amount the loan receives
+        let checking = &r["Checking"];
+        let posted = checking
+            .payments
+            .iter()
+            .find(|p| {
+                p.date == first_payment.date && p.cash_flow.name == first_payment.cash_flow.name
+            })
+            .unwrap();
+        assert_eq!(posted.amount, -first_payment.amount);
+    }
 }
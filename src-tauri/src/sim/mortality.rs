@@ -0,0 +1,335 @@
+use super::cash::{CashFlow, Payment};
+use super::household::{Household, LifeEventKind};
+use crate::error::Error;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use ndarray::Array1;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+// Annual probabilities of death by age from a Gompertz-Makeham curve fitted to approximate a
+// recent US period life table. These are synthetic rates, not published ones, and should be
+// replaced with the SSA period life table. Ages run from 0 to 120, where death is certain.
+const PERIOD_LIFE_TABLE: &str = include_str!("data/period_life_table.csv");
+
+// The calendar year the bundled table describes, used as the base year for improvement.
+const PERIOD_LIFE_TABLE_YEAR: i32 = 2020;
+
+/// Stochastic longevity settings for a `Scenario`.
+///
+/// When set, the death date of every household member with a `mortality_table` is sampled
+/// separately on each Monte Carlo path. `improvement` is the annual rate at which mortality
+/// falls after the table's base year, e.g. `0.01` for 1% a year.
+///
+/// Any cash flow that starts or ends on a `Death` event is resolved on each path, and income on
+/// an account with an `owner` stops when the owner dies.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Longevity {
+    pub improvement: f64,
+}
+
+/// The period life tables bundled with the app.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, JsonSchema, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum BundledTable {
+    Male,
+    Female,
+}
+
+/// A period life table giving the probability of dying within a year at each age.
+#[derive(Debug, Clone)]
+pub struct MortalityTable {
+    pub name: String,
+    pub base_year: i32,
+    pub qx: Vec<f64>,
+}
+
+impl MortalityTable {
+    /// Loads one of the bundled tables.
+    pub fn bundled(table: BundledTable) -> Result<MortalityTable, Error> {
+        let (name, column) = match table {
+            BundledTable::Male => ("Male", 1),
+            BundledTable::Female => ("Female", 2),
+        };
+        let qx = PERIOD_LIFE_TABLE
+            .lines()
+            .skip(1)
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                l.split(',')
+                    .nth(column)
+                    .and_then(|q| q.parse::<f64>().ok())
+                    .ok_or(Error::Parse(format!("Bad period life table row: {}", l)))
+            })
+            .collect::<Result<Vec<f64>, Error>>()?;
+        Ok(MortalityTable {
+            name: name.to_string(),
+            base_year: PERIOD_LIFE_TABLE_YEAR,
+            qx,
+        })
+    }
+
+    /// Probability of dying within the year at `age`, during calendar `year`.
+    pub fn q(&self, age: u32, year: i32, improvement: f64) -> f64 {
+        let q = self.qx[(age as usize).min(self.qx.len() - 1)];
+        if q >= 1.0 {
+            return 1.0;
+        }
+        let years = (year - self.base_year).max(0);
+        q * (1.0 - improvement).powi(years)
+    }
+
+    /// Expected remaining years of life at `age` in calendar `year`.
+    pub fn life_expectancy(&self, age: u32, year: i32, improvement: f64) -> f64 {
+        let mut alive = 1.0;
+        let mut expected = 0.0;
+        for (i, x) in (age..self.qx.len() as u32).enumerate() {
+            let q = self.q(x, year + i as i32, improvement);
+            expected += alive * (1.0 - q / 2.0);
+            alive *= 1.0 - q;
+        }
+        expected
+    }
+
+    /// Samples a death date for someone born on `birth_date` who is alive on `from`.
+    pub fn sample_death_date<R: Rng + ?Sized>(
+        &self,
+        birth_date: NaiveDate,
+        from: NaiveDate,
+        improvement: f64,
+        rng: &mut R,
+    ) -> Result<NaiveDate, Error> {
+        let mut age = from.year() - birth_date.year();
+        if (from.month(), from.day()) < (birth_date.month(), birth_date.day()) {
+            age -= 1;
+        }
+        let age = age.max(0) as u32;
+
+        let mut years = 0;
+        loop {
+            let q = self.q(age + years, from.year() + years as i32, improvement);
+            if rng.gen::<f64>() < q {
+                // Deaths are spread evenly through the year of age
+                let out_of_range = || {
+                    Error::Simulation(format!(
+                        "A death date sampled {} years after {} is out of range",
+                        years, from
+                    ))
+                };
+                let start = from
+                    .checked_add_months(Months::new(12 * years))
+                    .ok_or_else(out_of_range)?;
+                let end = start
+                    .checked_add_months(Months::new(12))
+                    .ok_or_else(out_of_range)?;
+                let day = rng.gen_range(0..(end - start).num_days());
+                return Ok(start + Duration::days(day));
+            }
+            years += 1;
+        }
+    }
+}
+
+/// Whether a cash flow's dates depend on when someone dies once longevity is stochastic.
+///
+/// Flows tagged `"Income"` on an account with an owner implicitly end on the owner's death.
+pub fn depends_on_death(cash_flow: &CashFlow, owner: Option<&str>) -> bool {
+    let on_death = |e: &Option<_>| {
+        matches!(
+            e,
+            Some(super::household::LifeEvent {
+                kind: LifeEventKind::Death,
+                ..
+            })
+        )
+    };
+    on_death(&cash_flow.start_event)
+        || on_death(&cash_flow.end_event)
+        || ends_with_owner(cash_flow, owner)
+}
+
+fn ends_with_owner(cash_flow: &CashFlow, owner: Option<&str>) -> bool {
+    let income = cash_flow
+        .tags
+        .as_ref()
+        .is_some_and(|t| t.iter().any(|t| t == "Income"));
+    income && owner.is_some() && cash_flow.end_event.is_none()
+}
+
+/// Death dates of each household member on every path.
+///
+/// Members with a `mortality_table` get a date sampled on each path, conditional on being alive
+/// at the start of the scenario. Everyone else dies at their `life_expectancy`, if they have one.
+#[derive(Debug, Clone)]
+pub struct Lifetimes {
+    pub death_dates: HashMap<String, Vec<Option<NaiveDate>>>,
+}
+
+impl Lifetimes {
+    pub fn sample<R: Rng + ?Sized>(
+        household: &Household,
+        longevity: &Longevity,
+        from: NaiveDate,
+        num_samples: usize,
+        rng: &mut R,
+    ) -> Result<Lifetimes, Error> {
+        let mut death_dates = HashMap::new();
+        for person in &household.members {
+            let dates = match &person.mortality_table {
+                Some(name) => {
+                    let table = MortalityTable::bundled(*name)?;
+                    (0..num_samples)
+                        .map(|_| {
+                            table
+                                .sample_death_date(
+                                    person.birth_date,
+                                    from.max(person.birth_date),
+                                    longevity.improvement,
+                                    rng,
+                                )
+                                .map(Some)
+                        })
+                        .collect::<Result<Vec<Option<NaiveDate>>, Error>>()?
+                }
+                None => vec![person.death_date()?; num_samples],
+            };
+            death_dates.insert(person.name.clone(), dates);
+        }
+        Ok(Lifetimes { death_dates })
+    }
+
+    pub fn death_date(&self, person: &str, path: usize) -> Option<NaiveDate> {
+        self.death_dates.get(person).and_then(|d| d[path])
+    }
+}
+
+/// Cash flows resolved separately on every path, stored as per-path amounts by date.
+#[derive(Debug, Clone)]
+pub struct PathFlows {
+    flows: Vec<CashFlow>,
+    amounts: HashMap<NaiveDate, Vec<(usize, Array1<f64>)>>,
+}
+
+impl PathFlows {
+    pub fn new(
+        flows: Vec<CashFlow>,
+        owner: Option<&str>,
+        household: &Household,
+        lifetimes: &Lifetimes,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        num_samples: usize,
+    ) -> Result<PathFlows, Error> {
+        let mut amounts: HashMap<NaiveDate, Vec<(usize, Array1<f64>)>> = HashMap::new();
+        for (i, cf) in flows.iter().enumerate() {
+            let mut cf = cf.clone();
+            if ends_with_owner(&cf, owner) {
+                cf.end_event = Some(super::household::LifeEvent::new(None, LifeEventKind::Death));
+            }
+            for path in 0..num_samples {
+                let resolved = household.resolve_cash_flow_with(&cf, owner, |p| {
+                    Ok(lifetimes.death_date(&p.name, path))
+                })?;
+                let from = resolved
+                    .start_date
+                    .map_or(start_date, |s| s.max(start_date));
+                let to = resolved.end_date.map_or(end_date, |e| e.min(end_date));
+                if from > to {
+                    continue;
+                }
+                for p in resolved
+                    .payments(from, to, false)
+                    .into_iter()
+                    .chain(resolved.payments(from, to, true))
+                {
+                    let on_date = amounts.entry(p.date).or_default();
+                    let slot = match on_date.iter().position(|(j, _)| *j == i) {
+                        Some(slot) => slot,
+                        None => {
+                            on_date.push((i, Array1::zeros(num_samples)));
+                            on_date.len() - 1
+                        }
+                    };
+                    on_date[slot].1[path] += p.amount;
+                }
+            }
+        }
+        Ok(PathFlows { flows, amounts })
+    }
+
+    /// The per-path total of the flows on `d`, and a payment of the mean amount for each flow.
+    pub fn at(&self, d: NaiveDate, num_samples: usize) -> (Array1<f64>, Vec<Payment>) {
+        let mut total = Array1::zeros(num_samples);
+        let mut payments = vec![];
+        for (i, amounts) in self.amounts.get(&d).into_iter().flatten() {
+            total += amounts;
+            payments.push(Payment::new(
+                d,
+                amounts.mean().unwrap(),
+                self.flows[*i].clone(),
+            ));
+        }
+        (total, payments)
+    }
+
+    /// The per-path total of all flows on or before `d`.
+    pub fn total_through(&self, d: NaiveDate, num_samples: usize) -> Array1<f64> {
+        let mut total = Array1::zeros(num_samples);
+        for (_, amounts) in self.amounts.iter().filter(|(date, _)| **date <= d) {
+            for (_, a) in amounts {
+                total += a;
+            }
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_bundled_tables() {
+        let male = MortalityTable::bundled(BundledTable::Male).unwrap();
+        let female = MortalityTable::bundled(BundledTable::Female).unwrap();
+        assert_eq!(male.qx.len(), 121);
+        assert_eq!(female.qx.len(), 121);
+        assert!(male
+            .qx
+            .iter()
+            .chain(&female.qx)
+            .all(|q| (0.0..=1.0).contains(q)));
+        assert_eq!(male.q(120, 2020, 0.0), 1.0);
+
+        let m = male.life_expectancy(65, 2020, 0.0);
+        let f = female.life_expectancy(65, 2020, 0.0);
+        assert!(m > 15.0 && m < 22.0);
+        assert!(f > m);
+
+        // Improvement lowers future mortality and raises life expectancy
+        assert!(male.q(80, 2040, 0.01) < male.q(80, 2020, 0.01));
+        assert!(male.life_expectancy(65, 2040, 0.01) > m);
+    }
+
+    #[test]
+    fn test_sample_death_date() {
+        let table = MortalityTable::bundled(BundledTable::Female).unwrap();
+        let birth = NaiveDate::from_ymd_opt(1959, 5, 1).unwrap();
+        let from = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+
+        let n = 2_000;
+        let mut total_years = 0.0;
+        for _ in 0..n {
+            let d = table.sample_death_date(birth, from, 0.0, &mut rng).unwrap();
+            assert!(d >= from);
+            total_years += (d - from).num_days() as f64 / 365.25;
+        }
+        let expected = table.life_expectancy(65, 2024, 0.0);
+        assert!((total_years / n as f64 - expected).abs() < 1.0);
+    }
+}
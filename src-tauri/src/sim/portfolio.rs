@@ -1,8 +1,10 @@
 use super::cash::Account;
 use super::cash::Frequency;
+use super::validation::Problem;
 use ndarray::Array1;
 use ndarray_rand::rand_distr::Normal;
 use ndarray_rand::RandomExt;
+use rand::Rng;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -76,6 +78,49 @@ impl Portfolio {
             weights: vec![1.0],
         })
     }
+
+    /// Checks that there is one weight per asset, that the weights are zero or more and sum to 1,
+    /// and that every asset's returns can be sampled.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        if self.weights.len() != self.assets.len() {
+            problems.push(Problem::new(
+                "weights",
+                format!(
+                    "has {} entries for {} assets",
+                    self.weights.len(),
+                    self.assets.len()
+                ),
+            ));
+        }
+        for (i, w) in self.weights.iter().enumerate() {
+            if !(w.is_finite() && *w >= 0.0) {
+                problems.push(Problem::new(
+                    &format!("weights[{}]", i),
+                    "must be zero or more",
+                ));
+            }
+        }
+        let total: f64 = self.weights.iter().sum();
+        if (total - 1.0).abs() > 1e-6 {
+            problems.push(Problem::new("weights", format!("sum to {}, not 1", total)));
+        }
+        for (i, asset) in self.assets.iter().enumerate() {
+            if !asset.mean_return.is_finite() {
+                problems.push(Problem::new(
+                    &format!("assets[{}].mean_return", i),
+                    "is not a number",
+                ));
+            }
+            if !(asset.std_dev.is_finite() && asset.std_dev >= 0.0) {
+                problems.push(Problem::new(
+                    &format!("assets[{}].std_dev", i),
+                    "must be zero or more",
+                ));
+            }
+        }
+        problems
+    }
 }
 
 #[allow(unused)]
@@ -105,6 +150,25 @@ pub trait Invest {
         nsamples: &usize,
         period: &Frequency,
     ) -> Array1<f64>;
+
+    /// Like `invest`, but draws returns from `rng` so that seeded simulations are reproducible.
+    fn invest_using<R: Rng + ?Sized>(
+        &self,
+        starting_balance: &Array1<f64>,
+        portfolio: &Portfolio,
+        nsamples: &usize,
+        period: &Frequency,
+        rng: &mut R,
+    ) -> Array1<f64>;
+
+    fn invest_asset_using<R: Rng + ?Sized>(
+        &self,
+        asset: &Asset,
+        weight: &f64,
+        nsamples: &usize,
+        period: &Frequency,
+        rng: &mut R,
+    ) -> Array1<f64>;
 }
 
 impl Invest for Account {
@@ -116,6 +180,33 @@ impl Invest for Account {
         portfolio: &Portfolio,
         nsamples: &usize,
         period: &Frequency,
+    ) -> Array1<f64> {
+        self.invest_using(
+            starting_balance,
+            portfolio,
+            nsamples,
+            period,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    fn invest_asset(
+        &self,
+        asset: &Asset,
+        weight: &f64,
+        nsamples: &usize,
+        period: &Frequency,
+    ) -> Array1<f64> {
+        self.invest_asset_using(asset, weight, nsamples, period, &mut rand::thread_rng())
+    }
+
+    fn invest_using<R: Rng + ?Sized>(
+        &self,
+        starting_balance: &Array1<f64>,
+        portfolio: &Portfolio,
+        nsamples: &usize,
+        period: &Frequency,
+        rng: &mut R,
     ) -> Array1<f64> {
         let ai = portfolio.assets.iter();
         let wi = portfolio.weights.iter();
@@ -123,24 +214,25 @@ impl Invest for Account {
 
         let mut ret = Array1::<f64>::zeros(*nsamples);
         for (a, w) in it {
-            ret = ret + self.invest_asset(a, w, nsamples, period);
+            ret = ret + self.invest_asset_using(a, w, nsamples, period, rng);
         }
         starting_balance.clone() * (1.0 + ret)
     }
 
-    fn invest_asset(
+    fn invest_asset_using<R: Rng + ?Sized>(
         &self,
         asset: &Asset,
         weight: &f64,
         nsamples: &usize,
         period: &Frequency,
+        rng: &mut R,
     ) -> Array1<f64> {
         let normal = Normal::new(
             asset.mean_return * period.fraction(),
             asset.std_dev * period.fraction().sqrt(),
         )
         .unwrap(); // handle the Result here
-        let a = Array1::<f64>::random(*nsamples, normal);
+        let a = Array1::<f64>::random_using(*nsamples, normal, rng);
         *weight * &a
     }
 }
@@ -149,6 +241,37 @@ impl Invest for Account {
 mod invest_tests {
     use super::*;
 
+    #[test]
+    fn test_validate() {
+        let assets = vec![
+            Asset::new("Stocks".to_string(), 0.07, 0.15),
+            Asset::new("Bonds".to_string(), 0.03, 0.05),
+        ];
+        assert!(Portfolio::new(assets.clone(), vec![0.6, 0.4])
+            .validate()
+            .is_empty());
+        assert!(!Portfolio::new(assets.clone(), vec![1.0])
+            .validate()
+            .is_empty());
+        assert!(!Portfolio::new(assets.clone(), vec![0.6, 0.6])
+            .validate()
+            .is_empty());
+
+        let problems = Portfolio::new(assets.clone(), vec![1.5, -0.5]).validate();
+        assert_eq!(
+            problems,
+            vec![Problem::new("weights[1]", "must be zero or more")]
+        );
+
+        let mut volatile = assets;
+        volatile[1].std_dev = -0.05;
+        let problems = Portfolio::new(volatile, vec![0.6, 0.4]).validate();
+        assert_eq!(
+            problems,
+            vec![Problem::new("assets[1].std_dev", "must be zero or more")]
+        );
+    }
+
     #[test]
     fn test_invest() {
         let account = Account::new(
@@ -0,0 +1,263 @@
+use super::metrics::Metric;
+use super::solver::DEFAULT_SEED;
+use super::{simulate, Scenario};
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A numeric scenario input that can be perturbed. Cash flows and assets are identified by their
+/// position in the account's `cash_flows` and in the portfolio's `assets`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum Input {
+    CashFlowAmount { account: String, cash_flow: usize },
+    CashFlowTaxRate { account: String, cash_flow: usize },
+    MeanReturn { account: String, asset: usize },
+    StdDev { account: String, asset: usize },
+    Inflation,
+}
+
+impl Input {
+    /// Every non-zero numeric input of `scenario`.
+    pub fn all(scenario: &Scenario) -> Vec<Input> {
+        let mut inputs = vec![];
+        for a in &scenario.accounts {
+            let account = a.account.name.clone();
+            for (i, cf) in a.account.cash_flows.iter().enumerate() {
+                if cf.amount != 0.0 {
+                    inputs.push(Input::CashFlowAmount {
+                        account: account.clone(),
+                        cash_flow: i,
+                    });
+                }
+                if cf.tax_rate != 0.0 {
+                    inputs.push(Input::CashFlowTaxRate {
+                        account: account.clone(),
+                        cash_flow: i,
+                    });
+                }
+            }
+            for (i, asset) in a.portfolio.iter().flat_map(|p| &p.assets).enumerate() {
+                if asset.mean_return != 0.0 {
+                    inputs.push(Input::MeanReturn {
+                        account: account.clone(),
+                        asset: i,
+                    });
+                }
+                if asset.std_dev != 0.0 {
+                    inputs.push(Input::StdDev {
+                        account: account.clone(),
+                        asset: i,
+                    });
+                }
+            }
+        }
+        if scenario.inflation != 0.0 {
+            inputs.push(Input::Inflation);
+        }
+        inputs
+    }
+
+    /// A reference to this input's value in `scenario`.
+    fn value_mut<'a>(&self, scenario: &'a mut Scenario) -> Result<&'a mut f64, Error> {
+        let (account, index) = match self {
+            Input::Inflation => return Ok(&mut scenario.inflation),
+            Input::CashFlowAmount { account, cash_flow }
+            | Input::CashFlowTaxRate { account, cash_flow } => (account, *cash_flow),
+            Input::MeanReturn { account, asset } | Input::StdDev { account, asset } => {
+                (account, *asset)
+            }
+        };
+        let a = scenario
+            .accounts
+            .iter_mut()
+            .find(|a| &a.account.name == account)
+            .ok_or(Error::Validation(format!("Unknown account: {}", account)))?;
+        let missing =
+            || Error::Validation(format!("Unknown input of account {}: {}", account, index));
+
+        match self {
+            Input::CashFlowAmount { .. } => a
+                .account
+                .cash_flows
+                .get_mut(index)
+                .map(|cf| &mut cf.amount)
+                .ok_or_else(missing),
+            Input::CashFlowTaxRate { .. } => a
+                .account
+                .cash_flows
+                .get_mut(index)
+                .map(|cf| &mut cf.tax_rate)
+                .ok_or_else(missing),
+            Input::MeanReturn { .. } => a
+                .portfolio
+                .as_mut()
+                .and_then(|p| p.assets.get_mut(index))
+                .map(|asset| &mut asset.mean_return)
+                .ok_or_else(missing),
+            Input::StdDev { .. } => a
+                .portfolio
+                .as_mut()
+                .and_then(|p| p.assets.get_mut(index))
+                .map(|asset| &mut asset.std_dev)
+                .ok_or_else(missing),
+            Input::Inflation => unreachable!(),
+        }
+    }
+
+    /// A copy of `scenario` with this input scaled by `factor`.
+    pub fn scale(&self, scenario: &Scenario, factor: f64) -> Result<Scenario, Error> {
+        let mut scenario = scenario.clone();
+        *self.value_mut(&mut scenario)? *= factor;
+        Ok(scenario)
+    }
+
+    /// A readable name for the input, for labelling charts.
+    pub fn label(&self, scenario: &Scenario) -> String {
+        let account = |name: &String| scenario.accounts.iter().find(|a| &a.account.name == name);
+        let cash_flow_name = |name: &String, i: usize| {
+            account(name)
+                .and_then(|a| a.account.cash_flows.get(i))
+                .and_then(|cf| cf.name.clone())
+                .unwrap_or(format!("Cash Flow {}", i + 1))
+        };
+        let asset_name = |name: &String, i: usize| {
+            account(name)
+                .and_then(|a| a.portfolio.as_ref())
+                .and_then(|p| p.assets.get(i))
+                .map(|asset| asset.name.clone())
+                .unwrap_or(format!("Asset {}", i + 1))
+        };
+
+        match self {
+            Input::CashFlowAmount { account, cash_flow } => {
+                format!("{}: {}", account, cash_flow_name(account, *cash_flow))
+            }
+            Input::CashFlowTaxRate { account, cash_flow } => format!(
+                "{}: {} Tax Rate",
+                account,
+                cash_flow_name(account, *cash_flow)
+            ),
+            Input::MeanReturn { account, asset } => {
+                format!("{}: {} Return", account, asset_name(account, *asset))
+            }
+            Input::StdDev { account, asset } => {
+                format!("{}: {} Volatility", account, asset_name(account, *asset))
+            }
+            Input::Inflation => "Inflation".to_string(),
+        }
+    }
+}
+
+/// The `metric` with an input decreased (`low`) and increased (`high`) by the perturbation.
+/// `swing` is the absolute difference between the two, and sets the input's rank.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Sensitivity {
+    pub input: Input,
+    pub label: String,
+    pub low: f64,
+    pub high: f64,
+    pub swing: f64,
+}
+
+/// Tornado chart data: the sensitivity of `metric` to every input, largest swing first, around
+/// its `base` value.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct SensitivityAnalysis {
+    pub metric: Metric,
+    pub perturbation: f64,
+    pub base: f64,
+    pub sensitivities: Vec<Sensitivity>,
+}
+
+/// Reruns `scenario` with each of its numeric inputs scaled down and up by `perturbation`, e.g.
+/// `0.1` for ±10%, and measures the change in `metric`.
+///
+/// Every run uses the scenario's `seed`, or a fixed one when it has none, so that differences
+/// come from the inputs rather than from the random paths. Inputs that are zero are skipped.
+pub fn analyze(
+    scenario: &Scenario,
+    metric: &Metric,
+    perturbation: f64,
+) -> Result<SensitivityAnalysis, Error> {
+    let mut scenario = scenario.clone();
+    scenario.seed.get_or_insert(DEFAULT_SEED);
+    let evaluate = |s: Scenario| metric.evaluate(&simulate(s)?);
+
+    let base = evaluate(scenario.clone())?;
+    let mut sensitivities = vec![];
+    for input in Input::all(&scenario) {
+        let low = evaluate(input.scale(&scenario, 1.0 - perturbation)?)?;
+        let high = evaluate(input.scale(&scenario, 1.0 + perturbation)?)?;
+        sensitivities.push(Sensitivity {
+            label: input.label(&scenario),
+            input,
+            low,
+            high,
+            swing: (high - low).abs(),
+        });
+    }
+    sensitivities.sort_by(|a, b| b.swing.total_cmp(&a.swing));
+
+    Ok(SensitivityAnalysis {
+        metric: metric.clone(),
+        perturbation,
+        base,
+        sensitivities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario
+    }
+
+    #[test]
+    fn test_inputs() {
+        let mut scenario = scenario();
+        let inputs = Input::all(&scenario);
+
+        // Four cash flows, and a tax rate on the income
+        assert_eq!(inputs.len(), 5);
+        assert_eq!(inputs[1].label(&scenario), "Example: Income Tax Rate");
+
+        scenario.accounts[0].portfolio = crate::sim::portfolio::Portfolio::default();
+        scenario.inflation = 0.03;
+        let inputs = Input::all(&scenario);
+        assert_eq!(inputs.len(), 8);
+        assert_eq!(inputs[5].label(&scenario), "Example: Equities Return");
+
+        let scaled = inputs[6].scale(&scenario, 2.0).unwrap();
+        assert_eq!(
+            scaled.accounts[0].portfolio.as_ref().unwrap().assets[0].std_dev,
+            0.3
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let metric = Metric::TerminalBalance {
+            account: "Example".to_string(),
+            percentile: 0.5,
+        };
+        let analysis = analyze(&scenario(), &metric, 0.1).unwrap();
+        assert_eq!(analysis.sensitivities.len(), 5);
+
+        // The 10,000 vacation moves the ending balance the most
+        let top = &analysis.sensitivities[0];
+        assert_eq!(top.label, "Example: Vacation");
+        assert!((top.low - (analysis.base + 1_000.0)).abs() < 1e-6);
+        assert!((top.high - (analysis.base - 1_000.0)).abs() < 1e-6);
+        assert!(analysis
+            .sensitivities
+            .windows(2)
+            .all(|w| w[0].swing >= w[1].swing));
+    }
+}
@@ -0,0 +1,288 @@
+use super::metrics::Metric;
+use super::withdrawal::WithdrawalStrategy;
+use super::{goal, simulate, Scenario, SimulationResult};
+use crate::error::Error;
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+const MAX_ITERATIONS: u32 = 100;
+
+// Seed used when the scenario has none, so that every evaluation sees the same market paths
+pub(crate) const DEFAULT_SEED: u64 = 0;
+
+/// The scenario input a solver varies.
+///
+/// - `CashFlowAmount` is the annual amount of the cash flow named `cash_flow` in `account`.
+/// - `CashFlowEndDate` is the date that cash flow ends, e.g. when a salary stops at retirement.
+/// - `AccountEndDate` is the end date of `account`. The scenario runs until the latest account
+///   end date.
+/// - `WithdrawalRate` is the rate of the `WithdrawalPolicy` named `policy`: the `rate` of a
+///   fixed percentage or floor-and-ceiling strategy, or the `initial_rate` of Guyton-Klinger.
+///
+/// Dates are solved in whole days after the scenario's start date.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum SolveFor {
+    CashFlowAmount { account: String, cash_flow: String },
+    CashFlowEndDate { account: String, cash_flow: String },
+    AccountEndDate { account: String },
+    WithdrawalRate { policy: String },
+}
+
+impl SolveFor {
+    fn is_date(&self) -> bool {
+        matches!(
+            self,
+            SolveFor::CashFlowEndDate { .. } | SolveFor::AccountEndDate { .. }
+        )
+    }
+
+    /// The date a solved value refers to, for date inputs.
+    pub fn date(&self, scenario: &Scenario, value: f64) -> Option<NaiveDate> {
+        if self.is_date() {
+            Some(scenario.start_date + Duration::days(value.round() as i64))
+        } else {
+            None
+        }
+    }
+
+    /// A copy of `scenario` with this input set to `value`.
+    pub fn apply(&self, scenario: &Scenario, value: f64) -> Result<Scenario, Error> {
+        let mut scenario = scenario.clone();
+        let date = self.date(&scenario, value);
+        match self {
+            SolveFor::CashFlowAmount { account, cash_flow }
+            | SolveFor::CashFlowEndDate { account, cash_flow } => {
+                let a = find_account(&mut scenario, account)?;
+                let cf = a
+                    .cash_flows
+                    .iter_mut()
+                    .find(|cf| cf.name.as_ref() == Some(cash_flow))
+                    .ok_or(Error::Validation(format!(
+                        "Unknown cash flow: {}",
+                        cash_flow
+                    )))?;
+                match date {
+                    Some(_) => cf.end_date = date,
+                    None => cf.amount = value,
+                }
+            }
+            SolveFor::AccountEndDate { account } => {
+                find_account(&mut scenario, account)?.end_date = date.unwrap();
+                scenario.end_date = scenario
+                    .accounts
+                    .iter()
+                    .map(|a| a.account.end_date)
+                    .max()
+                    .unwrap();
+            }
+            SolveFor::WithdrawalRate { policy } => {
+                let w = scenario
+                    .withdrawals
+                    .iter_mut()
+                    .find(|w| &w.name == policy)
+                    .ok_or(Error::Validation(format!(
+                        "Unknown withdrawal policy: {}",
+                        policy
+                    )))?;
+                match &mut w.strategy {
+                    WithdrawalStrategy::FixedPercentage { rate }
+                    | WithdrawalStrategy::FloorAndCeiling { rate, .. }
+                    | WithdrawalStrategy::GuytonKlinger {
+                        initial_rate: rate, ..
+                    } => *rate = value,
+                    WithdrawalStrategy::ConstantDollar { .. } => {
+                        return Err(Error::Validation(format!(
+                            "Withdrawal policy {} has no rate",
+                            policy
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(scenario)
+    }
+}
+
+fn find_account<'a>(
+    scenario: &'a mut Scenario,
+    name: &str,
+) -> Result<&'a mut super::cash::Account, Error> {
+    scenario
+        .accounts
+        .iter_mut()
+        .map(|a| &mut a.account)
+        .find(|a| a.name == name)
+        .ok_or(Error::Validation(format!("Unknown account: {}", name)))
+}
+
+/// The result of `solve`: the input `value` (and its `date`, for date inputs) that meets the
+/// target, the `metric` it gives, and the simulation `results` at that value.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Solution {
+    pub value: f64,
+    pub date: Option<NaiveDate>,
+    pub metric: f64,
+    pub iterations: u32,
+    pub results: HashMap<String, SimulationResult>,
+}
+
+/// Finds the value of `variable` between `lower` and `upper` at which `metric` just reaches
+/// `target`, by bisection to within `tolerance`.
+///
+/// The target must be met at exactly one of the bounds, and the metric should change
+/// monotonically in between. The returned value is the one closest to the other bound that still
+/// meets the target, e.g. the smallest savings amount or the earliest retirement date. Scenarios
+/// without a `seed` are given one, so that every run sees the same random paths.
+pub fn solve(
+    scenario: &Scenario,
+    variable: &SolveFor,
+    metric: &Metric,
+    target: f64,
+    lower: f64,
+    upper: f64,
+    tolerance: f64,
+) -> Result<Solution, Error> {
+    let mut scenario = scenario.clone();
+    scenario.seed.get_or_insert(DEFAULT_SEED);
+
+    let round = |x: f64| if variable.is_date() { x.round() } else { x };
+    let evaluate = |x: f64| -> Result<(f64, HashMap<String, SimulationResult>), Error> {
+        let results = simulate(variable.apply(&scenario, x)?)?;
+        Ok((metric.evaluate(&results)?, results))
+    };
+    let (value, metric, mut results, iterations) = bisect(
+        evaluate,
+        target,
+        round(lower),
+        round(upper),
+        tolerance,
+        round,
+    )?;
+    // Goal contributions are solved by bisection too, so only for the solution
+    goal::size_contributions(&variable.apply(&scenario, value)?, &mut results)?;
+    Ok(Solution {
+        value,
+        date: variable.date(&scenario, value),
+        metric,
+        iterations,
+        results,
+    })
+}
+
+/// The bisection behind `solve`, over any `evaluate` that gives a metric and the results it came
+/// from. Returns the value that meets `target`, its metric and results, and the number of
+/// iterations. `round` snaps each midpoint, e.g. to whole days.
+pub(crate) fn bisect<R>(
+    evaluate: impl Fn(f64) -> Result<(f64, R), Error>,
+    target: f64,
+    lower: f64,
+    upper: f64,
+    tolerance: f64,
+    round: impl Fn(f64) -> f64,
+) -> Result<(f64, f64, R, u32), Error> {
+    let (lower_metric, lower_results) = evaluate(lower)?;
+    let (upper_metric, upper_results) = evaluate(upper)?;
+    let mut met = match (lower_metric >= target, upper_metric >= target) {
+        (true, false) => (lower, lower_metric, lower_results),
+        (false, true) => (upper, upper_metric, upper_results),
+        (true, true) => {
+            return Err(Error::Simulation(
+                "The target is met at both bounds".to_string(),
+            ))
+        }
+        (false, false) => {
+            return Err(Error::Simulation(
+                "The target is not met at either bound".to_string(),
+            ))
+        }
+    };
+    let mut missed = if met.0 == lower { upper } else { lower };
+
+    let mut iterations = 0;
+    while (met.0 - missed).abs() > tolerance && iterations < MAX_ITERATIONS {
+        let mid = round((met.0 + missed) / 2.0);
+        if mid == met.0 || mid == missed {
+            break;
+        }
+        let (m, results) = evaluate(mid)?;
+        if m >= target {
+            met = (mid, m, results);
+        } else {
+            missed = mid;
+        }
+        iterations += 1;
+    }
+
+    let (value, metric, results) = met;
+    Ok((value, metric, results, iterations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::run_simulation;
+
+    fn scenario() -> Scenario {
+        let mut scenario = crate::sim::examples::two_account_scenario::two_account_scenario();
+        scenario.accounts.truncate(1);
+        scenario
+    }
+
+    fn solvent() -> Metric {
+        Metric::SuccessProbability {
+            account: "Example".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_solve_cash_flow_amount() {
+        let variable = SolveFor::CashFlowAmount {
+            account: "Example".to_string(),
+            cash_flow: "Income".to_string(),
+        };
+        let s = solve(&scenario(), &variable, &solvent(), 1.0, 0.0, 20_000.0, 1.0).unwrap();
+
+        // By the 10,000 vacation on Nov 5, 20 paychecks taxed at 20% need to cover 14,583 of
+        // spending less the 10,000 starting balance
+        assert!((s.value - 6_875.0).abs() < 2.0);
+        assert_eq!(s.metric, 1.0);
+        assert!(s.date.is_none());
+        assert!(s.results.contains_key("Example"));
+    }
+
+    #[test]
+    fn test_solve_end_date() {
+        let mut scenario = scenario();
+        scenario.accounts[0].account.cash_flows[0].amount = 12_000.0;
+        let variable = SolveFor::CashFlowEndDate {
+            account: "Example".to_string(),
+            cash_flow: "Income".to_string(),
+        };
+        let s = solve(&scenario, &variable, &solvent(), 1.0, 0.0, 365.0, 1.0).unwrap();
+        let date = s.date.unwrap();
+        assert!(date > scenario.start_date && date < scenario.end_date);
+
+        // Income stopping a day earlier would overdraw the account
+        let earlier = variable.apply(&scenario, s.value - 1.0).unwrap();
+        let r = run_simulation(earlier).unwrap();
+        assert!(solvent().evaluate(&r).unwrap() < 1.0);
+    }
+
+    #[test]
+    fn test_solve_requires_bracket() {
+        let variable = SolveFor::CashFlowAmount {
+            account: "Example".to_string(),
+            cash_flow: "Income".to_string(),
+        };
+        assert!(solve(&scenario(), &variable, &solvent(), 1.0, 0.0, 1_000.0, 1.0).is_err());
+
+        let unknown = SolveFor::WithdrawalRate {
+            policy: "Retirement".to_string(),
+        };
+        assert!(unknown.apply(&scenario(), 0.04).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::error::Error;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Something wrong with an input, and where: `path` names the field, such as
+/// `cash_flows[2].start_date`, or is empty for the input as a whole.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct Problem {
+    pub path: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(path: &str, message: impl Into<String>) -> Problem {
+        Problem {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Moves problems found in a part of an input under that part's path.
+pub fn nested(prefix: &str, problems: Vec<Problem>) -> Vec<Problem> {
+    problems
+        .into_iter()
+        .map(|p| Problem {
+            path: match p.path.as_str() {
+                "" => prefix.to_string(),
+                path => format!("{}.{}", prefix, path),
+            },
+            message: p.message,
+        })
+        .collect()
+}
+
+/// A validation error listing every problem, or `Ok` if there are none.
+pub fn check(problems: Vec<Problem>) -> Result<(), Error> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let messages: Vec<String> = problems
+        .iter()
+        .map(|p| match p.path.as_str() {
+            "" => p.message.clone(),
+            path => format!("{}: {}", path, p.message),
+        })
+        .collect();
+    Err(Error::Validation(messages.join("; ")))
+}
+
+#[test]
+fn test_check() {
+    assert!(check(vec![]).is_ok());
+
+    let problems = nested(
+        "accounts[0]",
+        vec![
+            Problem::new("", "has no name"),
+            Problem::new("balance", "is not a number"),
+        ],
+    );
+    assert_eq!(problems[1].path, "accounts[0].balance");
+    assert_eq!(
+        check(problems),
+        Err(Error::Validation(
+            "accounts[0]: has no name; accounts[0].balance: is not a number".to_string()
+        ))
+    );
+}
@@ -0,0 +1,446 @@
+use super::cash::{CashFlow, Frequency, Payment};
+use chrono::{Months, NaiveDate};
+use ndarray::{Array1, Zip};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// How the annual spending amount of a `WithdrawalPolicy` is set each policy year.
+///
+/// - `ConstantDollar` spends `amount` in the first year and raises it with inflation.
+/// - `FixedPercentage` spends `rate` of the balance at the start of each year.
+/// - `GuytonKlinger` starts at `initial_rate` of the balance and raises spending with inflation,
+///   skipping the raise after a losing year. Spending is cut by `adjustment` when the current
+///   withdrawal rate rises more than `upper_guardrail` above the initial rate, and raised by
+///   `adjustment` when it falls more than `lower_guardrail` below it.
+/// - `FloorAndCeiling` spends `rate` of the balance, but never less than `floor` below or more
+///   than `ceiling` above the inflation-adjusted first-year amount.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub enum WithdrawalStrategy {
+    ConstantDollar {
+        amount: f64,
+    },
+    FixedPercentage {
+        rate: f64,
+    },
+    GuytonKlinger {
+        initial_rate: f64,
+        upper_guardrail: f64,
+        lower_guardrail: f64,
+        adjustment: f64,
+    },
+    FloorAndCeiling {
+        rate: f64,
+        floor: f64,
+        ceiling: f64,
+    },
+}
+
+/// `WithdrawalPolicy` draws down `account` during decumulation, separately on every Monte Carlo
+/// path. Spending is set once per policy year, on the anniversary of `start_date`, and paid out
+/// on `frequency` like a `CashFlow`. Withdrawals never take more than the available balance.
+///
+/// A policy that started before the simulation sets its first year's spending on the first
+/// simulated day, and `Once` and `Annually` policies pay out on the days spending is set, so a
+/// policy starting on February 29 still pays every year.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct WithdrawalPolicy {
+    pub name: String,
+    pub account: String,
+    pub strategy: WithdrawalStrategy,
+    pub frequency: Frequency,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+}
+
+impl WithdrawalPolicy {
+    pub fn new(
+        name: String,
+        account: String,
+        strategy: WithdrawalStrategy,
+        frequency: Frequency,
+        start_date: NaiveDate,
+        end_date: Option<NaiveDate>,
+    ) -> WithdrawalPolicy {
+        WithdrawalPolicy {
+            name,
+            account,
+            strategy,
+            frequency,
+            start_date,
+            end_date,
+        }
+    }
+
+    pub fn cash_flow(&self, date: NaiveDate, amount: f64) -> CashFlow {
+        CashFlow::new(
+            Some(self.name.clone()),
+            amount,
+            Some(Frequency::Once),
+            Some(date),
+            Some(date),
+            None,
+            Some(vec!["Withdrawal".to_string()]),
+        )
+    }
+
+    /// The first anniversary of `start_date` after `d`. Anniversaries of February 29 fall on
+    /// February 28 in other years.
+    fn anniversary_after(&self, d: &NaiveDate) -> Option<NaiveDate> {
+        (1..)
+            .map_while(|n| self.start_date.checked_add_months(Months::new(12 * n)))
+            .find(|a| a > d)
+    }
+}
+
+/// Summary of how much annual spending varied, across every path and policy year.
+///
+/// `mean_path_variation` is the average, over paths, of each path's coefficient of variation of
+/// annual spending. `largest_cut` is the worst year-over-year decline in annual spending seen on
+/// any path, as a fraction of the prior year.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct WithdrawalStats {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean_path_variation: f64,
+    pub largest_cut: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/rustTypes/")]
+pub struct WithdrawalResult {
+    pub policy: WithdrawalPolicy,
+    pub paths: Vec<Vec<Payment>>,
+    pub stats: WithdrawalStats,
+}
+
+/// Per-path state of a `WithdrawalPolicy` during `run_simulation`.
+pub struct WithdrawalState {
+    pub policy: WithdrawalPolicy,
+    years: i32,
+    next_reset: Option<NaiveDate>,
+    spending: Array1<f64>,
+    initial_spending: Array1<f64>,
+    last_balance: Array1<f64>,
+    paths: Vec<Vec<Payment>>,
+    annual: Vec<Vec<f64>>,
+}
+
+impl WithdrawalState {
+    pub fn new(policy: WithdrawalPolicy, num_samples: usize) -> WithdrawalState {
+        WithdrawalState {
+            policy,
+            years: -1,
+            next_reset: None,
+            spending: Array1::zeros(num_samples),
+            initial_spending: Array1::zeros(num_samples),
+            last_balance: Array1::zeros(num_samples),
+            paths: vec![vec![]; num_samples],
+            annual: vec![vec![]; num_samples],
+        }
+    }
+
+    fn reset_spending(&mut self, balance: &Array1<f64>, inflation: f64) {
+        self.years += 1;
+        let first_year = self.years == 0;
+        let inflation_factor = (1.0 + inflation).powi(self.years);
+
+        self.spending = match &self.policy.strategy {
+            WithdrawalStrategy::ConstantDollar { amount } => {
+                Array1::zeros(balance.len()) + *amount * inflation_factor
+            }
+            WithdrawalStrategy::FixedPercentage { rate } => balance.mapv(|b| (b * rate).max(0.0)),
+            WithdrawalStrategy::GuytonKlinger {
+                initial_rate,
+                upper_guardrail,
+                lower_guardrail,
+                adjustment,
+            } => {
+                if first_year {
+                    balance.mapv(|b| (b * initial_rate).max(0.0))
+                } else {
+                    let mut spending = self.spending.clone();
+                    Zip::from(&mut spending)
+                        .and(balance)
+                        .and(&self.last_balance)
+                        .for_each(|s, &b, &last| {
+                            // Skip the inflation raise after a losing year if the withdrawal
+                            // rate is above where it started
+                            let lost = b < last;
+                            if !(lost && b > 0.0 && *s / b > *initial_rate) {
+                                *s *= 1.0 + inflation;
+                            }
+                            if b <= 0.0 {
+                                return;
+                            }
+                            let rate = *s / b;
+                            if rate > initial_rate * (1.0 + upper_guardrail) {
+                                *s *= 1.0 - adjustment;
+                            } else if rate < initial_rate * (1.0 - lower_guardrail) {
+                                *s *= 1.0 + adjustment;
+                            }
+                        });
+                    spending
+                }
+            }
+            WithdrawalStrategy::FloorAndCeiling {
+                rate,
+                floor,
+                ceiling,
+            } => {
+                if first_year {
+                    balance.mapv(|b| (b * rate).max(0.0))
+                } else {
+                    let mut spending = balance.mapv(|b| (b * rate).max(0.0));
+                    Zip::from(&mut spending)
+                        .and(&self.initial_spending)
+                        .for_each(|s, &initial| {
+                            let real = initial * inflation_factor;
+                            *s = s.clamp(real * (1.0 - floor), real * (1.0 + ceiling));
+                        });
+                    spending
+                }
+            }
+        };
+
+        if first_year {
+            self.initial_spending = self.spending.clone();
+        }
+        self.last_balance = balance.clone();
+        for a in self.annual.iter_mut() {
+            a.push(0.0);
+        }
+    }
+
+    /// Returns the amount withdrawn on each path on `d`, or `None` if the policy does not pay
+    /// out on this date.
+    pub fn withdraw(
+        &mut self,
+        d: &NaiveDate,
+        balance: &Array1<f64>,
+        inflation: f64,
+    ) -> Option<Array1<f64>> {
+        if *d < self.policy.start_date || self.policy.end_date.is_some_and(|e| *d > e) {
+            return None;
+        }
+        let reset = self.years < 0 || self.next_reset.is_some_and(|r| *d >= r);
+        if reset {
+            self.reset_spending(balance, inflation);
+            self.next_reset = self.policy.anniversary_after(d);
+        }
+        let pays = match self.policy.frequency {
+            Frequency::Once => reset && self.years == 0,
+            Frequency::Annually => reset,
+            _ => self.policy.frequency.matches(d, &None, &None),
+        };
+        if !pays {
+            return None;
+        }
+
+        let fraction = self.policy.frequency.fraction();
+        let mut amounts = Array1::zeros(balance.len());
+        Zip::from(&mut amounts)
+            .and(&self.spending)
+            .and(balance)
+            .for_each(|w, &s, &b| *w = (s * fraction).min(b.max(0.0)));
+
+        for (i, w) in amounts.iter().enumerate() {
+            self.paths[i].push(Payment::new(*d, -w, self.policy.cash_flow(*d, -w)));
+            *self.annual[i].last_mut().unwrap() += w;
+        }
+        Some(amounts)
+    }
+
+    pub fn into_result(self) -> WithdrawalResult {
+        let stats = withdrawal_stats(&self.annual);
+        WithdrawalResult {
+            policy: self.policy,
+            paths: self.paths,
+            stats,
+        }
+    }
+}
+
+fn withdrawal_stats(annual: &[Vec<f64>]) -> WithdrawalStats {
+    let all: Vec<f64> = annual.iter().flatten().cloned().collect();
+    if all.is_empty() {
+        return WithdrawalStats {
+            mean: 0.0,
+            std_dev: 0.0,
+            min: 0.0,
+            max: 0.0,
+            mean_path_variation: 0.0,
+            largest_cut: 0.0,
+        };
+    }
+
+    let (mean, std_dev) = mean_std(&all);
+    let variations: Vec<f64> = annual
+        .iter()
+        .map(|path| {
+            let (m, s) = mean_std(path);
+            if m > 0.0 {
+                s / m
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let largest_cut = annual
+        .iter()
+        .flat_map(|path| {
+            path.windows(2)
+                .filter(|w| w[0] > 0.0)
+                .map(|w| (w[0] - w[1]) / w[0])
+        })
+        .fold(0.0, f64::max);
+
+    WithdrawalStats {
+        mean,
+        std_dev,
+        min: all.iter().cloned().fold(f64::INFINITY, f64::min),
+        max: all.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        mean_path_variation: mean_std(&variations).0,
+        largest_cut,
+    }
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(strategy: WithdrawalStrategy, balances: &[f64]) -> WithdrawalResult {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let policy = WithdrawalPolicy::new(
+            "Retirement Spending".to_string(),
+            "Brokerage".to_string(),
+            strategy,
+            Frequency::Annually,
+            start,
+            None,
+        );
+        let mut state = WithdrawalState::new(policy, 1);
+        for (year, b) in balances.iter().enumerate() {
+            let d = NaiveDate::from_ymd_opt(2024 + year as i32, 1, 1).unwrap();
+            state.withdraw(&d, &(Array1::zeros(1) + *b), 0.03);
+        }
+        state.into_result()
+    }
+
+    fn spending(result: &WithdrawalResult) -> Vec<f64> {
+        result.paths[0].iter().map(|p| -p.amount).collect()
+    }
+
+    #[test]
+    fn test_constant_dollar() {
+        let r = run(
+            WithdrawalStrategy::ConstantDollar { amount: 40_000.0 },
+            &[1_000_000.0, 500_000.0, 1_500_000.0],
+        );
+        let s = spending(&r);
+        assert_eq!(s[0], 40_000.0);
+        assert!((s[2] - 40_000.0 * 1.03f64.powi(2)).abs() < 1e-6);
+        assert_eq!(r.stats.largest_cut, 0.0);
+    }
+
+    #[test]
+    fn test_fixed_percentage() {
+        let r = run(
+            WithdrawalStrategy::FixedPercentage { rate: 0.04 },
+            &[1_000_000.0, 500_000.0],
+        );
+        assert_eq!(spending(&r), vec![40_000.0, 20_000.0]);
+        assert!((r.stats.largest_cut - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_guyton_klinger_guardrails() {
+        let strategy = WithdrawalStrategy::GuytonKlinger {
+            initial_rate: 0.05,
+            upper_guardrail: 0.2,
+            lower_guardrail: 0.2,
+            adjustment: 0.1,
+        };
+
+        // A crash pushes the withdrawal rate through the upper guardrail, and spending is cut
+        let r = run(strategy.clone(), &[1_000_000.0, 600_000.0]);
+        let s = spending(&r);
+        assert_eq!(s[1], 50_000.0 * 0.9);
+
+        // A boom pushes it through the lower guardrail, and spending is raised
+        let r = run(strategy, &[1_000_000.0, 1_600_000.0]);
+        let s = spending(&r);
+        assert!((s[1] - 50_000.0 * 1.03 * 1.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_floor_and_ceiling() {
+        let r = run(
+            WithdrawalStrategy::FloorAndCeiling {
+                rate: 0.04,
+                floor: 0.1,
+                ceiling: 0.2,
+            },
+            &[1_000_000.0, 500_000.0, 2_000_000.0],
+        );
+        let s = spending(&r);
+        assert!((s[1] - 40_000.0 * 1.03 * 0.9).abs() < 1e-6);
+        assert!((s[2] - 40_000.0 * 1.03f64.powi(2) * 1.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_withdrawals_limited_to_balance() {
+        let r = run(
+            WithdrawalStrategy::ConstantDollar { amount: 40_000.0 },
+            &[10_000.0, -5_000.0],
+        );
+        assert_eq!(spending(&r), vec![10_000.0, 0.0]);
+    }
+
+    #[test]
+    fn test_leap_day_start_and_late_simulation_start() {
+        let policy = WithdrawalPolicy::new(
+            "Retirement Spending".to_string(),
+            "Brokerage".to_string(),
+            WithdrawalStrategy::ConstantDollar { amount: 40_000.0 },
+            Frequency::Annually,
+            NaiveDate::from_ymd_opt(2020, 2, 29).unwrap(),
+            None,
+        );
+        let mut state = WithdrawalState::new(policy, 1);
+        // The simulation starts years after the policy, in the middle of a policy year
+        let mut d = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        while d <= end {
+            state.withdraw(&d, &(Array1::zeros(1) + 1_000_000.0), 0.0);
+            d = d.succ_opt().unwrap();
+        }
+        let dates: Vec<NaiveDate> = state.into_result().paths[0]
+            .iter()
+            .map(|p| p.date)
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 2, 28).unwrap(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use super::{not_found, Storage};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Stores everything in folders under `root`.
+///
+/// Each item is a folder in its collection's folder, with one `<name>_<version>.yaml` file per
+/// version. Tombstones are folders in the collection's `.deleted` folder, and files are kept
+/// as-is in their folder.
+///
+/// Names are percent-encoded where they would otherwise escape their folder or be invalid on
+/// some platform, so "a/b" is saved as "a%2Fb".
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    pub fn new(root: PathBuf) -> FsStorage {
+        FsStorage { root }
+    }
+
+    fn collection_dir(&self, collection: &str) -> PathBuf {
+        self.root.join(collection)
+    }
+
+    fn item_dir(&self, collection: &str, name: &str) -> PathBuf {
+        self.collection_dir(collection).join(encode(name))
+    }
+
+    fn deleted_dir(&self, collection: &str) -> PathBuf {
+        self.collection_dir(collection).join(".deleted")
+    }
+
+    fn tombstone_dir(&self, collection: &str, tombstone: &str) -> PathBuf {
+        self.deleted_dir(collection).join(encode(tombstone))
+    }
+
+    fn version_path(&self, collection: &str, name: &str, version: &str) -> PathBuf {
+        self.item_dir(collection, name)
+            .join(version_file(name, version))
+    }
+}
+
+fn version_file(name: &str, version: &str) -> String {
+    format!("{}_{}.yaml", encode(name), encode(version))
+}
+
+/// Percent-encodes path separators, characters Windows doesn't allow in file names, control
+/// characters, `%` itself and a leading `.`, so that `..` and hidden names can't be saved.
+fn encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        let escape = matches!(
+            c,
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '%'
+        ) || c.is_control()
+            || (i == 0 && c == '.');
+        if escape {
+            encoded.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Reverses `encode`. A `%` that isn't followed by two hex digits is kept as-is.
+fn decode(encoded: &str) -> String {
+    let mut decoded = String::with_capacity(encoded.len());
+    let mut rest = encoded;
+    while let Some(i) = rest.find('%') {
+        decoded.push_str(&rest[..i]);
+        let escaped = rest
+            .get(i + 1..i + 3)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32);
+        match escaped {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[i + 3..];
+            }
+            None => {
+                decoded.push('%');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+// The names of the entries in `dir` that pass `filter`, or none if it doesn't exist
+fn entry_names(dir: &Path, filter: impl Fn(&Path) -> bool) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if filter(&path) {
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.is_file() && path.extension() == Some(OsStr::new("yaml"))
+}
+
+// The versions saved as `name` in `dir`
+fn versions_in(dir: &Path, name: &str) -> io::Result<Vec<String>> {
+    let prefix = format!("{}_", encode(name));
+    let files = entry_names(dir, is_yaml)?;
+    Ok(files
+        .iter()
+        .filter_map(|f| f.strip_suffix(".yaml")?.strip_prefix(&prefix))
+        .map(decode)
+        .collect())
+}
+
+impl Storage for FsStorage {
+    fn list(&self, collection: &str) -> io::Result<Vec<String>> {
+        let dir = self.collection_dir(collection);
+        let mut names = Vec::new();
+        for name in entry_names(&dir, |p| p.is_dir())? {
+            if !entry_names(&dir.join(&name), is_yaml)?.is_empty() {
+                names.push(decode(&name));
+            }
+        }
+        Ok(names)
+    }
+
+    fn versions(&self, collection: &str, name: &str) -> io::Result<Vec<String>> {
+        versions_in(&self.item_dir(collection, name), name)
+    }
+
+    fn read(&self, collection: &str, name: &str, version: &str) -> io::Result<String> {
+        fs::read_to_string(self.version_path(collection, name, version))
+    }
+
+    fn write(&self, collection: &str, name: &str, version: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(self.item_dir(collection, name))?;
+        fs::write(self.version_path(collection, name, version), contents)
+    }
+
+    fn remove(&self, collection: &str, name: &str, version: &str) -> io::Result<()> {
+        fs::remove_file(self.version_path(collection, name, version))?;
+        // Only succeeds once the last version is gone
+        let _ = fs::remove_dir(self.item_dir(collection, name));
+        Ok(())
+    }
+
+    fn delete(&self, collection: &str, name: &str, tombstone: &str) -> io::Result<()> {
+        let folder = self.item_dir(collection, name);
+        if !folder.is_dir() {
+            return Err(not_found(format!("No saved item {}", name)));
+        }
+
+        fs::create_dir_all(self.deleted_dir(collection))?;
+        // move the whole folder, versions and all, to its tombstone
+        fs::rename(folder, self.tombstone_dir(collection, tombstone))
+    }
+
+    fn tombstones(&self, collection: &str) -> io::Result<Vec<String>> {
+        let tombstones = entry_names(&self.deleted_dir(collection), |p| p.is_dir())?;
+        Ok(tombstones.iter().map(|t| decode(t)).collect())
+    }
+
+    fn deleted_versions(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+    ) -> io::Result<Vec<String>> {
+        versions_in(&self.tombstone_dir(collection, tombstone), original)
+    }
+
+    fn read_deleted(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+        version: &str,
+    ) -> io::Result<String> {
+        fs::read_to_string(
+            self.tombstone_dir(collection, tombstone)
+                .join(version_file(original, version)),
+        )
+    }
+
+    fn restore(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+        name: &str,
+    ) -> io::Result<()> {
+        let tombstone_dir = self.tombstone_dir(collection, tombstone);
+        if !tombstone_dir.is_dir() {
+            return Err(not_found(format!("No deleted item {}", tombstone)));
+        }
+
+        let folder = self.item_dir(collection, name);
+        fs::create_dir_all(&folder)?;
+        let prefix = format!("{}_", encode(original));
+        for file_name in entry_names(&tombstone_dir, |p| p.is_file())? {
+            let restored_name = match file_name.strip_prefix(&prefix) {
+                Some(version) => format!("{}_{}", encode(name), version),
+                None => file_name.clone(),
+            };
+            fs::rename(tombstone_dir.join(&file_name), folder.join(restored_name))?;
+        }
+        fs::remove_dir_all(tombstone_dir)
+    }
+
+    fn purge(&self, collection: &str, tombstone: &str) -> io::Result<()> {
+        let tombstone_dir = self.tombstone_dir(collection, tombstone);
+        if !tombstone_dir.is_dir() {
+            return Err(not_found(format!("No deleted item {}", tombstone)));
+        }
+        fs::remove_dir_all(tombstone_dir)
+    }
+
+    fn list_files(&self, folder: &str) -> io::Result<Vec<String>> {
+        let files = entry_names(&self.root.join(folder), |p| p.is_file())?;
+        Ok(files.iter().map(|f| decode(f)).collect())
+    }
+
+    fn read_file(&self, folder: &str, file: &str) -> io::Result<String> {
+        fs::read_to_string(self.root.join(folder).join(encode(file)))
+    }
+
+    fn write_file(&self, folder: &str, file: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(self.root.join(folder))?;
+        fs::write(self.root.join(folder).join(encode(file)), contents)
+    }
+}
+
+#[test]
+fn test_fs_storage() {
+    let root = std::env::temp_dir().join(format!("tortoise-fs-storage-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    super::test_storage(&FsStorage::new(root.clone()));
+
+    // Tombstones from before deletes were timestamped are named after the item
+    let legacy = root.join("accounts").join(".deleted").join("Old");
+    fs::create_dir_all(&legacy).unwrap();
+    fs::write(legacy.join("Old_20240101_000000.yaml"), "e").unwrap();
+    let storage = FsStorage::new(root.clone());
+    storage.restore("accounts", "Old", "Old", "Old").unwrap();
+    assert_eq!(
+        storage.read("accounts", "Old", "20240101_000000").unwrap(),
+        "e"
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_fs_storage_paths() {
+    assert_eq!(encode("../a/b"), "%2E.%2Fa%2Fb");
+    assert_eq!(encode("Joint: 50%"), "Joint%3A 50%25");
+    assert_eq!(encode("Checking"), "Checking");
+    for name in [
+        "../a/b",
+        "..",
+        ".hidden",
+        "C:\\Windows",
+        "100% <done>?",
+        "a%2Fb",
+        "tab\t",
+    ] {
+        assert_eq!(decode(&encode(name)), name);
+    }
+    assert_eq!(decode("50%"), "50%");
+
+    // Names can't escape their collection's folder
+    let root = std::env::temp_dir().join(format!("tortoise-paths-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let storage = FsStorage::new(root.join("data"));
+    storage
+        .write("accounts", "../../escaped", "v1", "a")
+        .unwrap();
+    storage.write("accounts", "..", "v1", "b").unwrap();
+    storage
+        .write_file("returns", "../escaped.csv", "c")
+        .unwrap();
+    assert!(!root.join("escaped").exists());
+    assert!(!root.join("data").join("escaped.csv").exists());
+    let mut names = storage.list("accounts").unwrap();
+    names.sort();
+    assert_eq!(names, vec!["..".to_string(), "../../escaped".to_string()]);
+    assert_eq!(
+        storage.read("accounts", "../../escaped", "v1").unwrap(),
+        "a"
+    );
+    assert_eq!(
+        storage.list_files("returns").unwrap(),
+        vec!["../escaped.csv"]
+    );
+
+    storage.delete("accounts", "..", "../1").unwrap();
+    assert_eq!(storage.tombstones("accounts").unwrap(), vec!["../1"]);
+    storage.restore("accounts", "../1", "..", "a/b").unwrap();
+    assert_eq!(storage.read("accounts", "a/b", "v1").unwrap(), "b");
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,196 @@
+use super::{not_found, Storage};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Mutex;
+
+// Versions of an item, by version
+type Versions = BTreeMap<String, String>;
+
+#[derive(Default)]
+struct Collections {
+    items: HashMap<String, BTreeMap<String, Versions>>,
+    deleted: HashMap<String, BTreeMap<String, Versions>>,
+    files: HashMap<String, BTreeMap<String, String>>,
+}
+
+/// Keeps everything in memory, for tests and throwaway sessions. Nothing outlives the process.
+#[derive(Default)]
+pub struct MemoryStorage {
+    collections: Mutex<Collections>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn list(&self, collection: &str) -> io::Result<Vec<String>> {
+        let c = self.collections.lock().unwrap();
+        Ok(c.items
+            .get(collection)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|(_, versions)| !versions.is_empty())
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn versions(&self, collection: &str, name: &str) -> io::Result<Vec<String>> {
+        let c = self.collections.lock().unwrap();
+        Ok(c.items
+            .get(collection)
+            .and_then(|items| items.get(name))
+            .map(|versions| versions.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn read(&self, collection: &str, name: &str, version: &str) -> io::Result<String> {
+        let c = self.collections.lock().unwrap();
+        c.items
+            .get(collection)
+            .and_then(|items| items.get(name))
+            .and_then(|versions| versions.get(version))
+            .cloned()
+            .ok_or_else(|| not_found(format!("No version {} of {}", version, name)))
+    }
+
+    fn write(&self, collection: &str, name: &str, version: &str, contents: &str) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        c.items
+            .entry(collection.to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default()
+            .insert(version.to_string(), contents.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, collection: &str, name: &str, version: &str) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        c.items
+            .get_mut(collection)
+            .and_then(|items| items.get_mut(name))
+            .and_then(|versions| versions.remove(version))
+            .map(|_| ())
+            .ok_or_else(|| not_found(format!("No version {} of {}", version, name)))
+    }
+
+    fn delete(&self, collection: &str, name: &str, tombstone: &str) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        let versions = c
+            .items
+            .get_mut(collection)
+            .and_then(|items| items.remove(name))
+            .ok_or_else(|| not_found(format!("No saved item {}", name)))?;
+        c.deleted
+            .entry(collection.to_string())
+            .or_default()
+            .insert(tombstone.to_string(), versions);
+        Ok(())
+    }
+
+    fn tombstones(&self, collection: &str) -> io::Result<Vec<String>> {
+        let c = self.collections.lock().unwrap();
+        Ok(c.deleted
+            .get(collection)
+            .map(|deleted| deleted.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn deleted_versions(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+    ) -> io::Result<Vec<String>> {
+        let c = self.collections.lock().unwrap();
+        Ok(c.deleted
+            .get(collection)
+            .and_then(|deleted| deleted.get(tombstone))
+            .map(|versions| versions.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn read_deleted(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+        version: &str,
+    ) -> io::Result<String> {
+        let c = self.collections.lock().unwrap();
+        c.deleted
+            .get(collection)
+            .and_then(|deleted| deleted.get(tombstone))
+            .and_then(|versions| versions.get(version))
+            .cloned()
+            .ok_or_else(|| not_found(format!("No version {} of {}", version, tombstone)))
+    }
+
+    fn restore(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+        name: &str,
+    ) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        let versions = c
+            .deleted
+            .get_mut(collection)
+            .and_then(|deleted| deleted.remove(tombstone))
+            .ok_or_else(|| not_found(format!("No deleted item {}", tombstone)))?;
+        c.items
+            .entry(collection.to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default()
+            .extend(versions);
+        Ok(())
+    }
+
+    fn purge(&self, collection: &str, tombstone: &str) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        c.deleted
+            .get_mut(collection)
+            .and_then(|deleted| deleted.remove(tombstone))
+            .map(|_| ())
+            .ok_or_else(|| not_found(format!("No deleted item {}", tombstone)))
+    }
+
+    fn list_files(&self, folder: &str) -> io::Result<Vec<String>> {
+        let c = self.collections.lock().unwrap();
+        Ok(c.files
+            .get(folder)
+            .map(|files| files.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn read_file(&self, folder: &str, file: &str) -> io::Result<String> {
+        let c = self.collections.lock().unwrap();
+        c.files
+            .get(folder)
+            .and_then(|files| files.get(file))
+            .cloned()
+            .ok_or_else(|| not_found(format!("No file {}", file)))
+    }
+
+    fn write_file(&self, folder: &str, file: &str, contents: &str) -> io::Result<()> {
+        let mut c = self.collections.lock().unwrap();
+        c.files
+            .entry(folder.to_string())
+            .or_default()
+            .insert(file.to_string(), contents.to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_memory_storage() {
+    super::test_storage(&MemoryStorage::new());
+}
@@ -0,0 +1,132 @@
+use std::io;
+
+pub mod fs;
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Where `io` keeps saved items and files.
+///
+/// Items, such as accounts, live in a `collection` under a `name`, with one serialized copy per
+/// saved version. Deleting an item moves all of its versions to a `tombstone`, from which they
+/// can be restored or purged. Version strings and tombstone names are chosen by `io`; a backend
+/// only stores them.
+///
+/// Files are unversioned documents in a `folder`, such as historical returns datasets.
+pub trait Storage: Send + Sync {
+    /// The names of the items in `collection` that have at least one version.
+    fn list(&self, collection: &str) -> io::Result<Vec<String>>;
+    /// The versions of an item, in any order. Unknown items have none.
+    fn versions(&self, collection: &str, name: &str) -> io::Result<Vec<String>>;
+    fn read(&self, collection: &str, name: &str, version: &str) -> io::Result<String>;
+    fn write(&self, collection: &str, name: &str, version: &str, contents: &str) -> io::Result<()>;
+    /// Removes a single version of an item.
+    fn remove(&self, collection: &str, name: &str, version: &str) -> io::Result<()>;
+
+    /// Moves every version of an item to `tombstone`.
+    fn delete(&self, collection: &str, name: &str, tombstone: &str) -> io::Result<()>;
+    fn tombstones(&self, collection: &str) -> io::Result<Vec<String>>;
+    /// The versions in `tombstone`, which were saved as the item `original`.
+    fn deleted_versions(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+    ) -> io::Result<Vec<String>>;
+    fn read_deleted(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+        version: &str,
+    ) -> io::Result<String>;
+    /// Moves every version in `tombstone`, which were saved as the item `original`, back to the
+    /// item `name`.
+    fn restore(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        original: &str,
+        name: &str,
+    ) -> io::Result<()>;
+    fn purge(&self, collection: &str, tombstone: &str) -> io::Result<()>;
+
+    /// The names of the files in `folder`.
+    fn list_files(&self, folder: &str) -> io::Result<Vec<String>>;
+    fn read_file(&self, folder: &str, file: &str) -> io::Result<String>;
+    fn write_file(&self, folder: &str, file: &str, contents: &str) -> io::Result<()>;
+}
+
+fn not_found(what: String) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, what)
+}
+
+// Checks shared by every backend, run against a fresh store
+#[cfg(test)]
+pub(crate) fn test_storage(storage: &dyn Storage) {
+    storage.write("accounts", "Checking", "v1", "a").unwrap();
+    storage.write("accounts", "Checking", "v2", "b").unwrap();
+    storage.write("accounts", "Savings", "v1", "c").unwrap();
+    storage.write("scenarios", "Retire", "v1", "d").unwrap();
+
+    let mut names = storage.list("accounts").unwrap();
+    names.sort();
+    assert_eq!(names, vec!["Checking".to_string(), "Savings".to_string()]);
+    let mut versions = storage.versions("accounts", "Checking").unwrap();
+    versions.sort();
+    assert_eq!(versions, vec!["v1".to_string(), "v2".to_string()]);
+    assert_eq!(storage.read("accounts", "Checking", "v2").unwrap(), "b");
+    assert!(storage.read("accounts", "Checking", "v3").is_err());
+    assert!(storage.versions("accounts", "Unknown").unwrap().is_empty());
+
+    storage.remove("accounts", "Checking", "v1").unwrap();
+    assert_eq!(
+        storage.versions("accounts", "Checking").unwrap(),
+        vec!["v2"]
+    );
+
+    storage
+        .delete("accounts", "Checking", "Checking_1")
+        .unwrap();
+    assert_eq!(
+        storage.list("accounts").unwrap(),
+        vec!["Savings".to_string()]
+    );
+    assert_eq!(storage.tombstones("accounts").unwrap(), vec!["Checking_1"]);
+    assert_eq!(
+        storage
+            .deleted_versions("accounts", "Checking_1", "Checking")
+            .unwrap(),
+        vec!["v2"]
+    );
+    assert_eq!(
+        storage
+            .read_deleted("accounts", "Checking_1", "Checking", "v2")
+            .unwrap(),
+        "b"
+    );
+    assert!(storage
+        .read_deleted("accounts", "Checking_1", "Checking", "v1")
+        .is_err());
+    assert!(storage.tombstones("scenarios").unwrap().is_empty());
+
+    storage
+        .restore("accounts", "Checking_1", "Checking", "Checking 2")
+        .unwrap();
+    assert_eq!(storage.read("accounts", "Checking 2", "v2").unwrap(), "b");
+    assert!(storage.tombstones("accounts").unwrap().is_empty());
+
+    storage.delete("accounts", "Savings", "Savings_1").unwrap();
+    storage.purge("accounts", "Savings_1").unwrap();
+    assert!(storage.tombstones("accounts").unwrap().is_empty());
+    assert!(storage.purge("accounts", "Savings_1").is_err());
+    assert!(storage.delete("accounts", "Savings", "Savings_2").is_err());
+
+    storage
+        .write_file("returns", "Crisis.csv", "month")
+        .unwrap();
+    assert_eq!(storage.list_files("returns").unwrap(), vec!["Crisis.csv"]);
+    assert_eq!(storage.read_file("returns", "Crisis.csv").unwrap(), "month");
+    assert!(storage.read_file("returns", "Other.csv").is_err());
+    assert!(storage.list_files("other").unwrap().is_empty());
+}
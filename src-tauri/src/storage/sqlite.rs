@@ -0,0 +1,258 @@
+use super::{not_found, Storage};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Keeps everything in a single SQLite database, with a table each for versions, tombstones and
+/// files.
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+fn to_io(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> io::Result<SqliteStorage> {
+        SqliteStorage::init(Connection::open(path).map_err(to_io)?)
+    }
+
+    pub fn open_in_memory() -> io::Result<SqliteStorage> {
+        SqliteStorage::init(Connection::open_in_memory().map_err(to_io)?)
+    }
+
+    fn init(conn: Connection) -> io::Result<SqliteStorage> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS versions (
+                collection TEXT NOT NULL,
+                name TEXT NOT NULL,
+                version TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                PRIMARY KEY (collection, name, version)
+            );
+            CREATE TABLE IF NOT EXISTS deleted (
+                collection TEXT NOT NULL,
+                tombstone TEXT NOT NULL,
+                version TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                PRIMARY KEY (collection, tombstone, version)
+            );
+            CREATE TABLE IF NOT EXISTS files (
+                folder TEXT NOT NULL,
+                name TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                PRIMARY KEY (folder, name)
+            );",
+        )
+        .map_err(to_io)?;
+        Ok(SqliteStorage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn strings(&self, sql: &str, params: impl rusqlite::Params) -> io::Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(sql).map_err(to_io)?;
+        let rows = stmt
+            .query_map(params, |row| row.get(0))
+            .map_err(to_io)?
+            .collect::<Result<Vec<String>, _>>();
+        rows.map_err(to_io)
+    }
+
+    fn execute(&self, sql: &str, params: impl rusqlite::Params) -> io::Result<usize> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(sql, params)
+            .map_err(to_io)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn list(&self, collection: &str) -> io::Result<Vec<String>> {
+        self.strings(
+            "SELECT DISTINCT name FROM versions WHERE collection = ?1 ORDER BY name",
+            params![collection],
+        )
+    }
+
+    fn versions(&self, collection: &str, name: &str) -> io::Result<Vec<String>> {
+        self.strings(
+            "SELECT version FROM versions WHERE collection = ?1 AND name = ?2",
+            params![collection, name],
+        )
+    }
+
+    fn read(&self, collection: &str, name: &str, version: &str) -> io::Result<String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT contents FROM versions
+                WHERE collection = ?1 AND name = ?2 AND version = ?3",
+                params![collection, name, version],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_io)?
+            .ok_or_else(|| not_found(format!("No version {} of {}", version, name)))
+    }
+
+    fn write(&self, collection: &str, name: &str, version: &str, contents: &str) -> io::Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO versions (collection, name, version, contents)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![collection, name, version, contents],
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, collection: &str, name: &str, version: &str) -> io::Result<()> {
+        let removed = self.execute(
+            "DELETE FROM versions WHERE collection = ?1 AND name = ?2 AND version = ?3",
+            params![collection, name, version],
+        )?;
+        match removed {
+            0 => Err(not_found(format!("No version {} of {}", version, name))),
+            _ => Ok(()),
+        }
+    }
+
+    fn delete(&self, collection: &str, name: &str, tombstone: &str) -> io::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(to_io)?;
+        let moved = tx
+            .execute(
+                "INSERT INTO deleted (collection, tombstone, version, contents)
+                SELECT collection, ?3, version, contents FROM versions
+                WHERE collection = ?1 AND name = ?2",
+                params![collection, name, tombstone],
+            )
+            .map_err(to_io)?;
+        if moved == 0 {
+            return Err(not_found(format!("No saved item {}", name)));
+        }
+        tx.execute(
+            "DELETE FROM versions WHERE collection = ?1 AND name = ?2",
+            params![collection, name],
+        )
+        .map_err(to_io)?;
+        tx.commit().map_err(to_io)
+    }
+
+    fn tombstones(&self, collection: &str) -> io::Result<Vec<String>> {
+        self.strings(
+            "SELECT DISTINCT tombstone FROM deleted WHERE collection = ?1 ORDER BY tombstone",
+            params![collection],
+        )
+    }
+
+    fn deleted_versions(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+    ) -> io::Result<Vec<String>> {
+        self.strings(
+            "SELECT version FROM deleted WHERE collection = ?1 AND tombstone = ?2",
+            params![collection, tombstone],
+        )
+    }
+
+    fn read_deleted(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+        version: &str,
+    ) -> io::Result<String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT contents FROM deleted
+                WHERE collection = ?1 AND tombstone = ?2 AND version = ?3",
+                params![collection, tombstone, version],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_io)?
+            .ok_or_else(|| not_found(format!("No version {} of {}", version, tombstone)))
+    }
+
+    fn restore(
+        &self,
+        collection: &str,
+        tombstone: &str,
+        _original: &str,
+        name: &str,
+    ) -> io::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(to_io)?;
+        let moved = tx
+            .execute(
+                "INSERT OR REPLACE INTO versions (collection, name, version, contents)
+                SELECT collection, ?3, version, contents FROM deleted
+                WHERE collection = ?1 AND tombstone = ?2",
+                params![collection, tombstone, name],
+            )
+            .map_err(to_io)?;
+        if moved == 0 {
+            return Err(not_found(format!("No deleted item {}", tombstone)));
+        }
+        tx.execute(
+            "DELETE FROM deleted WHERE collection = ?1 AND tombstone = ?2",
+            params![collection, tombstone],
+        )
+        .map_err(to_io)?;
+        tx.commit().map_err(to_io)
+    }
+
+    fn purge(&self, collection: &str, tombstone: &str) -> io::Result<()> {
+        let purged = self.execute(
+            "DELETE FROM deleted WHERE collection = ?1 AND tombstone = ?2",
+            params![collection, tombstone],
+        )?;
+        match purged {
+            0 => Err(not_found(format!("No deleted item {}", tombstone))),
+            _ => Ok(()),
+        }
+    }
+
+    fn list_files(&self, folder: &str) -> io::Result<Vec<String>> {
+        self.strings(
+            "SELECT name FROM files WHERE folder = ?1 ORDER BY name",
+            params![folder],
+        )
+    }
+
+    fn read_file(&self, folder: &str, file: &str) -> io::Result<String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT contents FROM files WHERE folder = ?1 AND name = ?2",
+                params![folder, file],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_io)?
+            .ok_or_else(|| not_found(format!("No file {}", file)))
+    }
+
+    fn write_file(&self, folder: &str, file: &str, contents: &str) -> io::Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO files (folder, name, contents) VALUES (?1, ?2, ?3)",
+            params![folder, file, contents],
+        )?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sqlite_storage() {
+    super::test_storage(&SqliteStorage::open_in_memory().unwrap());
+}
@@ -1,6 +1,10 @@
 import { invoke } from "@tauri-apps/api/core";
 import { Account } from "../rustTypes/Account";
+import { AccountDiff } from "../rustTypes/AccountDiff";
 import { CashFlow } from "../rustTypes/CashFlow";
+import { Deleted } from "../rustTypes/Deleted";
+import { Problem } from "../rustTypes/Problem";
+import { Version } from "../rustTypes/Version";
 
 export const listAccounts = async (): Promise<string[]> => {
   return invoke<string[]>("list_available_accounts");
@@ -31,8 +35,80 @@ export const saveAccount = async (account: Account): Promise<void> => {
   });
 };
 
+export const validateAccount = async (account: Account): Promise<Problem[]> => {
+  return invoke<Problem[]>("validate_account", {
+    account: JSON.stringify(account),
+  });
+};
+
 export const deleteAccount = async (accountName: string): Promise<void> => {
   await invoke<void>("delete_account", {
     accountName,
   });
 }
+
+export const listDeletedAccounts = async (): Promise<Deleted[]> => {
+  return invoke<Deleted[]>("list_deleted_accounts");
+};
+
+export const restoreDeletedAccount = async (
+  tombstone: string,
+): Promise<string> => {
+  return invoke<string>("restore_deleted_account", { tombstone });
+};
+
+export const purgeDeletedAccount = async (tombstone: string): Promise<void> => {
+  return invoke<void>("purge_deleted_account", { tombstone });
+};
+
+export const listAccountVersions = async (
+  accountName: string,
+): Promise<Version[]> => {
+  return invoke<Version[]>("list_account_versions", { accountName });
+};
+
+export const getAccountVersion = async (
+  accountName: string,
+  version: string,
+): Promise<Account> => {
+  return invoke<Account>("get_account_version", { accountName, version });
+};
+
+export const listDeletedAccounts = async (): Promise<Deleted[]> => {
+  return invoke<Deleted[]>("list_deleted_accounts");
+};
+
+export const restoreDeletedAccount = async (
+  tombstone: string,
+): Promise<string> => {
+  return invoke<string>("restore_deleted_account", { tombstone });
+};
+
+export const purgeDeletedAccount = async (tombstone: string): Promise<void> => {
+  return invoke<void>("purge_deleted_account", { tombstone });
+};
+
+export const listAccountVersionsToPrune = async (
+  accountName: string,
+): Promise<Version[]> => {
+  return invoke<Version[]>("list_account_versions_to_prune", { accountName });
+};
+
+export const diffAccountVersions = async (
+  accountName: string,
+  from: string,
+  to: string,
+): Promise<AccountDiff> => {
+  return invoke<AccountDiff>("diff_account_versions", {
+    accountName,
+    from,
+    to,
+  });
+};
+
+export const restoreAccountVersion = async (
+  accountName: string,
+  version: string,
+): Promise<Account> => {
+  return invoke<Account>("restore_account_version", { accountName, version });
+};
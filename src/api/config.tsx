@@ -0,0 +1,18 @@
+import { invoke } from "@tauri-apps/api/core";
+import { AppConfig } from "../rustTypes/AppConfig";
+
+export const getAppConfig = async (): Promise<AppConfig> => {
+  return invoke<AppConfig>("get_app_config");
+};
+
+export const saveAppConfig = async (config: AppConfig): Promise<void> => {
+  return invoke<void>("save_app_config", { config });
+};
+
+export const getDataDir = async (): Promise<string> => {
+  return invoke<string>("get_data_dir");
+};
+
+export const migrateDataDir = async (to: string): Promise<number> => {
+  return invoke<number>("migrate_data_dir", { to });
+};
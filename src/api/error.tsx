@@ -0,0 +1,10 @@
+import { AppError } from "../rustTypes/AppError";
+
+// Commands reject with an AppError, so this narrows what a caught error is.
+export const isAppError = (e: unknown): e is AppError => {
+  return typeof e === "object" && e !== null && "kind" in e && "message" in e;
+};
+
+export const errorMessage = (e: unknown): string => {
+  return isAppError(e) ? e.message : String(e);
+};
@@ -1,4 +1,11 @@
 import { invoke } from "@tauri-apps/api/core";
+import { Account } from "../rustTypes/Account";
+import { CsvImport } from "../rustTypes/CsvImport";
+import { CsvMapping } from "../rustTypes/CsvMapping";
+import { OfxStatement } from "../rustTypes/OfxStatement";
+import { Portfolio } from "../rustTypes/Portfolio";
+import { RecurringFlow } from "../rustTypes/RecurringFlow";
+import { Transaction } from "../rustTypes/Transaction";
 
 export const importAccount = async (filePath: string): Promise<string[]> => {
   return invoke<string[]>("import_account", { filePath });
@@ -7,4 +14,50 @@ export const importAccount = async (filePath: string): Promise<string[]> => {
 
 export const getFilePath = async (): Promise<string> => {
   return invoke<string>("get_file_path");
-}
\ No newline at end of file
+}
+
+export const readCsvHeaders = async (
+  filePath: string,
+  delimiter: string = ",",
+): Promise<string[]> => {
+  return invoke<string[]>("read_csv_headers", { filePath, delimiter });
+};
+
+export const importCsvTransactions = async (
+  filePath: string,
+  mapping: CsvMapping,
+): Promise<CsvImport> => {
+  return invoke<CsvImport>("import_csv_transactions", { filePath, mapping });
+};
+
+export const detectRecurringFlows = async (
+  transactions: Transaction[],
+): Promise<RecurringFlow[]> => {
+  return invoke<RecurringFlow[]>("detect_recurring_flows", { transactions });
+};
+
+export const importOfxStatements = async (
+  filePath: string,
+): Promise<OfxStatement[]> => {
+  return invoke<OfxStatement[]>("import_ofx_statements", { filePath });
+};
+
+export const accountFromOfxStatement = async (
+  statement: OfxStatement,
+  name: string,
+  endDate: string,
+): Promise<Account> => {
+  return invoke<Account>("account_from_ofx_statement", {
+    statement,
+    name,
+    endDate,
+  });
+};
+
+export const portfolioFromOfxStatement = async (
+  statement: OfxStatement,
+): Promise<Portfolio | null> => {
+  return invoke<Portfolio | null>("portfolio_from_ofx_statement", {
+    statement,
+  });
+};
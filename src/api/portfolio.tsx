@@ -1,5 +1,36 @@
 import { invoke } from "@tauri-apps/api/core";
+import { Portfolio } from "../rustTypes/Portfolio";
+import { Problem } from "../rustTypes/Problem";
 
 export const listPortfolios = async (): Promise<string[]> => {
   return invoke<string[]>("list_available_portfolios");
 };
+
+export const listPortfoliosDetail = async (): Promise<
+  Record<string, Portfolio>
+> => {
+  return invoke<Record<string, Portfolio>>("list_available_portfolios_detail");
+};
+
+export const getPortfolio = async (
+  portfolioName: string,
+): Promise<Portfolio> => {
+  return invoke<Portfolio>("get_portfolio_config", { portfolioName });
+};
+
+export const savePortfolio = async (
+  portfolioName: string,
+  portfolio: Portfolio,
+): Promise<void> => {
+  return invoke<void>("save_portfolio_config", { portfolioName, portfolio });
+};
+
+export const validatePortfolio = async (
+  portfolio: Portfolio,
+): Promise<Problem[]> => {
+  return invoke<Problem[]>("validate_portfolio", { portfolio });
+};
+
+export const deletePortfolio = async (portfolioName: string): Promise<void> => {
+  return invoke<void>("delete_portfolio", { portfolioName });
+};
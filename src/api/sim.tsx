@@ -1,15 +1,26 @@
 import { invoke } from "@tauri-apps/api/core";
+import { DebtPayoffPlan } from "../rustTypes/DebtPayoffPlan";
+import { Loan } from "../rustTypes/Loan";
+import { Metric } from "../rustTypes/Metric";
+import { PayoffStrategy } from "../rustTypes/PayoffStrategy";
+import { Problem } from "../rustTypes/Problem";
+import { RollingReplay } from "../rustTypes/RollingReplay";
+import { Scenario } from "../rustTypes/Scenario";
+import { ScenarioComparison } from "../rustTypes/ScenarioComparison";
+import { SensitivityAnalysis } from "../rustTypes/SensitivityAnalysis";
 import { SimulationResult } from "../rustTypes/SimulationResult";
+import { Solution } from "../rustTypes/Solution";
+import { SolveFor } from "../rustTypes/SolveFor";
 
 export type ScenarioResult = Record<string, SimulationResult>;
 
 export const runAccountSimulation = async (
   accountName: string,
-  portfolioFilename?: string,
+  portfolioName?: string,
 ): Promise<ScenarioResult> => {
   return invoke<ScenarioResult>("run_account_simulation", {
     accountName,
-    portfolioFilename: portfolioFilename || null,
+    portfolioName: portfolioName || null,
   });
 };
 
@@ -20,3 +31,122 @@ export const runScenarioSimulation = async (
     accountNames,
   });
 };
+
+export const compareDebtStrategies = async (
+  loans: Loan[],
+  monthlyBudget: number,
+  strategies: PayoffStrategy[],
+): Promise<DebtPayoffPlan[]> => {
+  return invoke<DebtPayoffPlan[]>("compare_debt_strategies", {
+    loans: JSON.stringify(loans),
+    monthlyBudget,
+    strategies,
+  });
+};
+
+export const solveScenario = async (
+  scenarioName: string,
+  variable: SolveFor,
+  metric: Metric,
+  target: number,
+  lower: number,
+  upper: number,
+  tolerance: number,
+): Promise<Solution> => {
+  return invoke<Solution>("solve_scenario", {
+    scenarioName,
+    variable,
+    metric,
+    target,
+    lower,
+    upper,
+    tolerance,
+  });
+};
+
+export const analyzeSensitivity = async (
+  scenarioName: string,
+  metric: Metric,
+  perturbation: number,
+): Promise<SensitivityAnalysis> => {
+  return invoke<SensitivityAnalysis>("analyze_sensitivity", {
+    scenarioName,
+    metric,
+    perturbation,
+  });
+};
+
+export const compareScenarios = async (
+  scenarioNames: string[],
+  seed?: number,
+): Promise<ScenarioComparison> => {
+  return invoke<ScenarioComparison>("compare_scenarios", {
+    scenarioNames,
+    seed: seed ?? null,
+  });
+};
+
+export const listReturnsDatasets = async (): Promise<string[]> => {
+  return invoke<string[]>("list_returns_datasets");
+};
+
+export const runHistoricalReplay = async (
+  scenarioName: string,
+  dataset: string,
+  windowStart: string,
+  from?: string,
+): Promise<ScenarioResult> => {
+  return invoke<ScenarioResult>("run_historical_replay", {
+    scenarioName,
+    dataset,
+    windowStart,
+    from: from || null,
+  });
+};
+
+export const runRollingReplay = async (
+  scenarioName: string,
+  dataset: string,
+  metric: Metric,
+): Promise<RollingReplay> => {
+  return invoke<RollingReplay>("run_rolling_replay", {
+    scenarioName,
+    dataset,
+    metric,
+  });
+};
+
+export const listSavedScenarios = async (): Promise<string[]> => {
+  return invoke<string[]>("list_saved_scenarios");
+};
+
+export const getSavedScenario = async (
+  scenarioName: string,
+): Promise<Scenario> => {
+  return invoke<Scenario>("get_saved_scenario", { scenarioName });
+};
+
+export const saveScenario = async (
+  scenarioName: string,
+  scenario: Scenario,
+): Promise<void> => {
+  return invoke<void>("save_scenario", { scenarioName, scenario });
+};
+
+export const validateScenario = async (
+  scenario: Scenario,
+): Promise<Problem[]> => {
+  return invoke<Problem[]>("validate_scenario", { scenario });
+};
+
+export const deleteSavedScenario = async (
+  scenarioName: string,
+): Promise<void> => {
+  return invoke<void>("delete_saved_scenario", { scenarioName });
+};
+
+export const runSavedScenario = async (
+  scenarioName: string,
+): Promise<ScenarioResult> => {
+  return invoke<ScenarioResult>("run_saved_scenario", { scenarioName });
+};
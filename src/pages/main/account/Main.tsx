@@ -193,6 +193,8 @@ const CashFlowCards: React.FC = () => {
               start_date: null,
               end_date: null,
               tags: [],
+              start_event: null,
+              end_event: null,
             });
             navigate(`/account/${name}/${cash_flows.length}`);
           }}
@@ -205,11 +205,14 @@ const Home: React.FC = () => {
       ))}
       <CreateAccountCard
         account={{
+          id: crypto.randomUUID(),
           name: "New Scenario",
           balance: 0,
           cash_flows: [],
           start_date: dayjs().format("YYYY-MM-DD"),
           end_date: dayjs().format("YYYY-MM-DD"),
+          interest: null,
+          owner: null,
         }}
         key={accounts.length + 1}
       />
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { CashFlow } from "./CashFlow";
+import type { InterestRate } from "./InterestRate";
 
-export interface Account { name: string, balance: number, cash_flows: Array<CashFlow>, start_date: string, end_date: string, }
\ No newline at end of file
+export interface Account { id: string, name: string, balance: number, cash_flows: Array<CashFlow>, start_date: string, end_date: string, interest: InterestRate | null, owner: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BalanceDelta } from "./BalanceDelta";
+
+export interface AccountDelta { account: string, deltas: Array<BalanceDelta>, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CashFlowDiff } from "./CashFlowDiff";
+import type { Change } from "./Change";
+
+export interface AccountDiff { account: string, change: Change, fields: Array<string>, cash_flows: Array<CashFlowDiff>, }
\ No newline at end of file
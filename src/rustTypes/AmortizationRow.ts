@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface AmortizationRow { date: string, rate: number, payment: number, interest: number, principal: number, extra_principal: number, balance: number, }
\ No newline at end of file
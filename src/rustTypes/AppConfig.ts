@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetentionPolicy } from "./RetentionPolicy";
+import type { StorageBackend } from "./StorageBackend";
+
+export interface AppConfig { retention: RetentionPolicy, storage: StorageBackend, data_dir: string | null, }
\ No newline at end of file
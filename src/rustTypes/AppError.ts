@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AppError = { "kind": "NotFound", "message": string } | { "kind": "Parse", "message": string } | { "kind": "Validation", "message": string } | { "kind": "Io", "message": string } | { "kind": "Simulation", "message": string };
\ No newline at end of file
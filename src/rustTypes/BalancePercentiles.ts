@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface BalancePercentiles { date: string, p10: number, p50: number, p90: number, }
\ No newline at end of file
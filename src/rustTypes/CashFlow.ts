@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Frequency } from "./Frequency";
+import type { LifeEvent } from "./LifeEvent";
 
-export interface CashFlow { name: string | null, amount: number, frequency: Frequency, start_date: string | null, end_date: string | null, tax_rate: number, tags: Array<string> | null, }
\ No newline at end of file
+export interface CashFlow { name: string | null, amount: number, frequency: Frequency, start_date: string | null, end_date: string | null, tax_rate: number, tags: Array<string> | null, start_event: LifeEvent | null, end_event: LifeEvent | null, }
\ No newline at end of file
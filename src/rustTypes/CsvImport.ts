@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Problem } from "./Problem";
+import type { Transaction } from "./Transaction";
+
+export interface CsvImport { transactions: Array<Transaction>, skipped: Array<Problem>, }
\ No newline at end of file
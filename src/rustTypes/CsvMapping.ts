@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AmountColumns } from "./AmountColumns";
+
+export interface CsvMapping { date: string, description: string, amount: AmountColumns, 
+date_format: string, delimiter: string, has_headers: boolean, }
\ No newline at end of file
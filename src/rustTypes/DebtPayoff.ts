@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DebtPayoff { name: string, payoff_date: string, total_interest: number, total_paid: number, }
\ No newline at end of file
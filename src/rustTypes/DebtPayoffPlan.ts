@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DebtPayoff } from "./DebtPayoff";
+import type { Loan } from "./Loan";
+import type { PayoffStrategy } from "./PayoffStrategy";
+
+export interface DebtPayoffPlan { strategy: PayoffStrategy, monthly_budget: number, payoff_date: string, total_interest: number, debts: Array<DebtPayoff>, loans: Array<Loan>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Goal } from "./Goal";
+
+export interface GoalResult { goal: Goal, probability: number, median_shortfall: number, required_monthly_contribution: number | null, }
\ No newline at end of file
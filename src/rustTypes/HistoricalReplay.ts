@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReturnsDataset } from "./ReturnsDataset";
+
+export interface HistoricalReplay { dataset: ReturnsDataset, window_start: string, from: string, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Person } from "./Person";
+
+export interface Household { members: Array<Person>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Input = { "CashFlowAmount": { account: string, cash_flow: number, } } | { "CashFlowTaxRate": { account: string, cash_flow: number, } } | { "MeanReturn": { account: string, asset: number, } } | { "StdDev": { account: string, asset: number, } } | "Inflation";
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Compounding } from "./Compounding";
+import type { DayCount } from "./DayCount";
+
+export interface InterestRate { rate: number, compounding: Compounding, day_count: DayCount, overdraft_rate: number | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LifeEventKind } from "./LifeEventKind";
+
+export interface LifeEvent { person: string | null, kind: LifeEventKind, offset_months: number, }
\ No newline at end of file
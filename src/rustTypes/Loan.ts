@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CashFlow } from "./CashFlow";
+import type { RateReset } from "./RateReset";
+
+export interface Loan { name: string, principal: number, rate: number, rate_resets: Array<RateReset>, term_months: number, start_date: string, extra_payments: Array<CashFlow>, funding_account: string | null, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OfxAccountKind } from "./OfxAccountKind";
+import type { Position } from "./Position";
+import type { Transaction } from "./Transaction";
+
+export interface OfxStatement { kind: OfxAccountKind, account_id: string, currency: string | null, balance: number | null, balance_date: string | null, transactions: Array<Transaction>, positions: Array<Position>, }
\ No newline at end of file
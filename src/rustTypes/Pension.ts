@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface Pension { name: string, annual_benefit: number, start_date: string, cola: number, end_date: string | null, survivor_percentage: number, survivor_end_date: string | null, tax_rate: number | null, beneficiary: string | null, survivor: string | null, }
\ No newline at end of file
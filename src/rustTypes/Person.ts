@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BundledTable } from "./BundledTable";
+
+export interface Person { name: string, birth_date: string, retirement_date: string | null, life_expectancy: number | null, mortality_table: BundledTable | null, }
\ No newline at end of file
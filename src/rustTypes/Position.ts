@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SecurityKind } from "./SecurityKind";
+
+export interface Position { security_id: string, name: string, ticker: string | null, kind: SecurityKind, units: number, unit_price: number, market_value: number, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Cadence } from "./Cadence";
+import type { CashFlow } from "./CashFlow";
+
+export interface RecurringFlow { payee: string, cadence: Cadence, occurrences: number, first_date: string, last_date: string, regularity: number, cash_flow: CashFlow, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Metric } from "./Metric";
+import type { ReplayOutcome } from "./ReplayOutcome";
+import type { SimulationResult } from "./SimulationResult";
+
+export interface RollingReplay { metric: Metric, outcomes: Array<ReplayOutcome>, worst: ReplayOutcome, worst_results: { [key in string]?: SimulationResult }, median: ReplayOutcome, median_results: { [key in string]?: SimulationResult }, }
\ No newline at end of file
@@ -1,5 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Goal } from "./Goal";
+import type { Household } from "./Household";
 import type { InvestedAccount } from "./InvestedAccount";
+import type { Loan } from "./Loan";
+import type { Longevity } from "./Longevity";
+import type { MarketModel } from "./MarketModel";
 import type { Transfer } from "./Transfer";
+import type { WithdrawalPolicy } from "./WithdrawalPolicy";
 
-export interface Scenario { accounts: Array<InvestedAccount>, transfers: Array<Transfer>, start_date: string, end_date: string, num_samples: number, }
\ No newline at end of file
+export interface Scenario { accounts: Array<InvestedAccount>, transfers: Array<Transfer>, loans: Array<Loan>, withdrawals: Array<WithdrawalPolicy>, goals: Array<Goal>, household: Household | null, longevity: Longevity | null, market: MarketModel, start_date: string, end_date: string, num_samples: number, inflation: number, 
+seed: number | null, }
\ No newline at end of file
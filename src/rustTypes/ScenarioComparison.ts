@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScenarioDelta } from "./ScenarioDelta";
+import type { SimulationResult } from "./SimulationResult";
+
+export interface ScenarioComparison { seed: number, results: Array<{ [key in string]?: SimulationResult }>, deltas: Array<ScenarioDelta>, }
\ No newline at end of file
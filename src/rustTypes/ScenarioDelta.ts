@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountDelta } from "./AccountDelta";
+import type { NetWorthDelta } from "./NetWorthDelta";
+import type { ScenarioDiff } from "./ScenarioDiff";
+
+export interface ScenarioDelta { scenario: number, diff: ScenarioDiff, net_worth: Array<NetWorthDelta>, accounts: Array<AccountDelta>, }
\ No newline at end of file
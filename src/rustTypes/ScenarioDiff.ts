@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountDiff } from "./AccountDiff";
+
+export interface ScenarioDiff { fields: Array<string>, accounts: Array<AccountDiff>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Input } from "./Input";
+
+export interface Sensitivity { input: Input, label: string, low: number, high: number, swing: number, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Metric } from "./Metric";
+import type { Sensitivity } from "./Sensitivity";
+
+export interface SensitivityAnalysis { metric: Metric, perturbation: number, base: number, sensitivities: Array<Sensitivity>, }
\ No newline at end of file
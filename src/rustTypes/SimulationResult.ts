@@ -1,5 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AccountBalance } from "./AccountBalance";
+import type { BalancePercentiles } from "./BalancePercentiles";
+import type { GoalResult } from "./GoalResult";
 import type { Payment } from "./Payment";
+import type { WithdrawalResult } from "./WithdrawalResult";
 
-export interface SimulationResult { balances: Array<AccountBalance>, uninvested_balances: Array<AccountBalance>, payments: Array<Payment>, }
\ No newline at end of file
+export interface SimulationResult { balances: Array<AccountBalance>, percentiles: Array<BalancePercentiles>, uninvested_balances: Array<AccountBalance>, payments: Array<Payment>, withdrawals: Array<WithdrawalResult>, goals: Array<GoalResult>, terminal_balances: Array<number>, minimum_balances: Array<number>, }
\ No newline at end of file
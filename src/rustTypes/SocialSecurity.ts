@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SocialSecurity { name: string, pia: number, birth_date: string, claiming_age: number, cola: number, end_date: string | null, tax_rate: number | null, beneficiary: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SimulationResult } from "./SimulationResult";
+
+export interface Solution { value: number, date: string | null, metric: number, iterations: number, results: { [key in string]?: SimulationResult }, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SolveFor = { "CashFlowAmount": { account: string, cash_flow: string, } } | { "CashFlowEndDate": { account: string, cash_flow: string, } } | { "AccountEndDate": { account: string, } } | { "WithdrawalRate": { policy: string, } };
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Frequency } from "./Frequency";
+import type { WithdrawalStrategy } from "./WithdrawalStrategy";
+
+export interface WithdrawalPolicy { name: string, account: string, strategy: WithdrawalStrategy, frequency: Frequency, start_date: string, end_date: string | null, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Payment } from "./Payment";
+import type { WithdrawalPolicy } from "./WithdrawalPolicy";
+import type { WithdrawalStats } from "./WithdrawalStats";
+
+export interface WithdrawalResult { policy: WithdrawalPolicy, paths: Array<Array<Payment>>, stats: WithdrawalStats, }
\ No newline at end of file
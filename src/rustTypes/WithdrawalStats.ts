@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface WithdrawalStats { mean: number, std_dev: number, min: number, max: number, mean_path_variation: number, largest_cut: number, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WithdrawalStrategy = { "ConstantDollar": { amount: number, } } | { "FixedPercentage": { rate: number, } } | { "GuytonKlinger": { initial_rate: number, upper_guardrail: number, lower_guardrail: number, adjustment: number, } } | { "FloorAndCeiling": { rate: number, floor: number, ceiling: number, } };
\ No newline at end of file
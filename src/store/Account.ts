@@ -30,11 +30,14 @@ type State = Account & {
 };
 
 export const useAccountStore = create<State>((set) => ({
+  id: crypto.randomUUID(),
   name: "",
   balance: 0,
   cash_flows: [],
   start_date: "",
   end_date: "",
+  interest: null,
+  owner: null,
   setName: (name) => set((state) => ({ ...state, name })),
   setStartDate: (start_date) => set((state) => ({ ...state, start_date })),
   setEndDate: (end_date) => set((state) => ({ ...state, end_date })),
@@ -42,7 +45,16 @@ export const useAccountStore = create<State>((set) => ({
   setAll: (account) => set(account),
   setAccount: (account) => set((_) => ({ ...account })),
   reset: () =>
-    set({ name: "", balance: 0, cash_flows: [], start_date: "", end_date: "" }),
+    set({
+      id: crypto.randomUUID(),
+      name: "",
+      balance: 0,
+      cash_flows: [],
+      start_date: "",
+      end_date: "",
+      interest: null,
+      owner: null,
+    }),
   addCashFlow: (cashFlow) =>
     set((state) => ({ ...state, cash_flows: [...state.cash_flows, cashFlow] })),
   removeCashFlow: (cashFlow) =>
@@ -61,6 +73,8 @@ export const useAccountStore = create<State>((set) => ({
         end_date: null,
         tax_rate: 0,
         tags: null,
+        start_event: null,
+        end_event: null,
       });
       return { ...state, cash_flows };
     }),